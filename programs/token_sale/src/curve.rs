@@ -0,0 +1,247 @@
+// Fixed-point sigmoid pricing curve.
+//
+// Pure integer math so off-chain quoting code can call `price_per_token` and get
+// exactly the price the program charges. No floating point, no Clock, no accounts.
+//
+//   price = min + (max - min) / (1 + e^(k * (amount - midpoint)))
+//
+// e^z is computed in 1e12 fixed point by range reduction (z = n*ln2 + r) and a
+// Taylor series for e^r, r in [0, ln2). Each series term truncates at most 1e-12,
+// so e^z carries a relative error below 1e-10. The curve value before rounding is
+// therefore within (max - min) * 1e-10 of the exact result, and the final price is
+// rounded up exactly once, in the treasury's favor. For any price range below 10^9
+// units that means the result is the exact price rounded up, or 1 unit (0.000001 EURC)
+// away from it when the exact price lies within 1e-6 of a whole unit.
+//
+// |z| is clamped at 50: beyond that 1 / (1 + e^z) is below 2e-22 and cannot move
+// the price by a single unit.
+//
+// Amounts are in base units of the sale token, so fractional purchases land on the
+// curve between their neighbouring whole-token prices.
+
+use crate::state::STEEPNESS_SCALE;
+
+const FP_SCALE: u128 = 1_000_000_000_000; // 1e12 fixed point
+const LN2_FP: u128 = 693_147_180_560; // ln(2) * 1e12
+const MAX_EXPONENT: u128 = 50; // |z| clamp (see above)
+const PRICE_FRAC: u128 = 1_000_000; // Extra price precision carried until the final rounding
+const BPS: u128 = 10_000;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct SigmoidCurve {
+    pub price_max: u64,
+    pub price_min: u64,
+    pub midpoint: u64,  // Tokens
+    pub steepness: u64, // Scaled by STEEPNESS_SCALE
+}
+
+// e^x for x >= 0, x and result in FP_SCALE fixed point. x must be <= MAX_EXPONENT.
+fn exp_fixed(x: u128) -> u128 {
+    let n = x / LN2_FP;
+    let r = x - n * LN2_FP;
+
+    // e^r = sum r^i / i!, r < ln2 so 16 terms are far past 1e-12
+    let mut sum = FP_SCALE;
+    let mut term = FP_SCALE;
+    for i in 1..=16u128 {
+        term = term * r / (FP_SCALE * i);
+        if term == 0 {
+            break;
+        }
+        sum += term;
+    }
+
+    // n <= 72, sum < 2e12 (< 2^41), so this stays below 2^113
+    sum << n
+}
+
+// Price per whole token (6 decimals) for a purchase of `amount` base units of a
+// token with `decimals` decimals, with a round discount of `discount_bps`, clamped
+// to [price_min, price_max]. None if `decimals` is too large to scale amounts.
+pub fn price_per_token(amount: u64, decimals: u8, curve: &SigmoidCurve, discount_bps: u16) -> Option<u64> {
+    let price_min = curve.price_min.min(curve.price_max);
+    let range = (curve.price_max - price_min) as u128;
+    let unit = 10u128.checked_pow(decimals as u32)?;
+
+    // |k * (amount - midpoint)| in FP_SCALE, clamped to MAX_EXPONENT
+    let amount = amount as u128;
+    let midpoint = (curve.midpoint as u128).checked_mul(unit)?;
+    let above_midpoint = amount >= midpoint;
+    let distance = amount.abs_diff(midpoint);
+    let k_distance = (curve.steepness as u128).saturating_mul(distance); // Scaled by STEEPNESS_SCALE * unit
+    let z = if k_distance >= (MAX_EXPONENT * STEEPNESS_SCALE as u128).checked_mul(unit)? {
+        MAX_EXPONENT * FP_SCALE
+    } else {
+        k_distance.checked_mul(FP_SCALE / STEEPNESS_SCALE as u128)? / unit
+    };
+    let e_z = exp_fixed(z);
+
+    // range / (1 + e^z), carried with PRICE_FRAC extra precision.
+    // For z < 0 use 1 / (1 + e^-|z|) = 1 - 1 / (1 + e^|z|).
+    let full = range * PRICE_FRAC;
+    let tail = full * FP_SCALE / (FP_SCALE + e_z);
+    let curve_part = if above_midpoint { tail } else { full - tail };
+
+    let base_price = price_min as u128 * PRICE_FRAC + curve_part;
+    let price = apply_discount(base_price, discount_bps);
+
+    Some((price as u64).max(price_min).min(curve.price_max))
+}
+
+// Apply the round discount to a PRICE_FRAC-scaled price and round up once, so the
+// charged price is never below the exact discounted value
+fn apply_discount(base_price: u128, discount_bps: u16) -> u128 {
+    let discount_multiplier = BPS - (discount_bps as u128).min(BPS);
+    (base_price * discount_multiplier).div_ceil(PRICE_FRAC * BPS)
+}
+
+// Total cost of `amount` base units at `price_per_token` per whole token, rounded
+// up so fractional purchases never undercharge. None on overflow.
+pub fn total_cost(amount: u64, price_per_token: u64, decimals: u8) -> Option<u64> {
+    let unit = 10u128.checked_pow(decimals as u32)?;
+    let total = (amount as u128 * price_per_token as u128).div_ceil(unit);
+    u64::try_from(total).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use proptest::prelude::*;
+
+    // The original on-chain f64 implementation, kept as the reference
+    fn reference_price(amount: u64, curve: &SigmoidCurve, discount_bps: u16) -> u64 {
+        let steepness = curve.steepness as f64 / STEEPNESS_SCALE as f64;
+        let exponent = steepness * (amount as f64 - curve.midpoint as f64);
+        let denominator = 1.0 + exponent.exp();
+        let price_range = (curve.price_max - curve.price_min) as f64;
+        let base_price = curve.price_min as f64 + (price_range / denominator);
+        let discounted = base_price * ((10_000 - discount_bps as u64) as f64 / 10_000.0);
+        (discounted.round() as u64).max(curve.price_min).min(curve.price_max)
+    }
+
+    fn default_curve() -> SigmoidCurve {
+        SigmoidCurve {
+            price_max: 290_000,
+            price_min: 180_000,
+            midpoint: 10_000,
+            steepness: 300_000, // 0.0003
+        }
+    }
+
+    fn arb_curve() -> impl Strategy<Value = SigmoidCurve> {
+        (1u64..10_000_000, 0u64..10_000_000, 0u64..1_000_000, 1u64..10_000_000).prop_map(
+            |(price_min, spread, midpoint, steepness)| SigmoidCurve {
+                price_max: price_min + spread,
+                price_min,
+                midpoint,
+                steepness,
+            },
+        )
+    }
+
+    #[test]
+    fn exp_matches_f64() {
+        for x in [0u128, 1, 500_000_000_000, LN2_FP, 3 * FP_SCALE, 20 * FP_SCALE, 50 * FP_SCALE] {
+            let expected = (x as f64 / FP_SCALE as f64).exp();
+            let actual = exp_fixed(x) as f64 / FP_SCALE as f64;
+            assert!(((actual - expected) / expected).abs() < 1e-10, "x={x}");
+        }
+    }
+
+    #[test]
+    fn default_curve_known_points() {
+        let curve = default_curve();
+        assert_eq!(price_per_token(10_000, 0, &curve, 0), Some(235_000));
+        // Exact values 284_783.15... and 256_303.49..., rounded up
+        assert_eq!(price_per_token(0, 0, &curve, 0), Some(284_784));
+        assert_eq!(price_per_token(1_000_000, 0, &curve, 0), Some(180_000));
+        assert_eq!(price_per_token(1, 0, &curve, 1_000), Some(256_304));
+    }
+
+    #[test]
+    fn fractional_amount_prices_between_whole_tokens() {
+        let curve = default_curve();
+        // 10_000.5 tokens with 6 decimals
+        let price = price_per_token(10_000_500_000, 6, &curve, 0).unwrap();
+        assert!(price <= price_per_token(10_000, 0, &curve, 0).unwrap());
+        assert!(price >= price_per_token(10_001, 0, &curve, 0).unwrap());
+    }
+
+    #[test]
+    fn discount_rounds_in_treasury_favor() {
+        // 5.000000 at 33.33% off = 3.3335 -> 4, where half-up rounding would give 3
+        assert_eq!(apply_discount(5 * PRICE_FRAC, 3_333), 4);
+        let flat_high = SigmoidCurve { price_max: 5, price_min: 1, midpoint: 1_000_000, steepness: 10_000_000 };
+        assert_eq!(price_per_token(0, 0, &flat_high, 3_333), Some(4));
+        // Exact results are not bumped
+        assert_eq!(apply_discount(5 * PRICE_FRAC, 0), 5);
+        assert_eq!(apply_discount(4 * PRICE_FRAC, 5_000), 2);
+    }
+
+    #[test]
+    fn oversized_decimals_are_rejected() {
+        let curve = default_curve();
+        assert_eq!(price_per_token(1, 39, &curve, 0), None);
+        assert_eq!(price_per_token(1, u8::MAX, &curve, 0), None);
+        assert_eq!(total_cost(1, 1, u8::MAX), None);
+    }
+
+    #[test]
+    fn total_cost_rounds_up() {
+        // 12.5 tokens (6 decimals) at 0.290001 per token = 3.6250125 -> 3.625013
+        assert_eq!(total_cost(12_500_000, 290_001, 6), Some(3_625_013));
+        assert_eq!(total_cost(12_000_000, 290_000, 6), Some(3_480_000));
+        assert_eq!(total_cost(1, 1, 9), Some(1));
+        assert_eq!(total_cost(u64::MAX, u64::MAX, 0), None);
+    }
+
+    proptest! {
+        #[test]
+        fn default_curve_within_one_unit_of_f64(amount in 0u64..5_000_000, discount_bps in 0u16..5_000) {
+            let curve = default_curve();
+            let fixed = price_per_token(amount, 0, &curve, discount_bps).unwrap();
+            let float = reference_price(amount, &curve, discount_bps);
+            prop_assert!(fixed.abs_diff(float) <= 1, "amount={} fixed={} float={}", amount, fixed, float);
+        }
+
+        #[test]
+        fn any_curve_within_one_unit_of_f64(curve in arb_curve(), amount in 0u64..10_000_000, discount_bps in 0u16..10_000) {
+            let fixed = price_per_token(amount, 0, &curve, discount_bps).unwrap();
+            let float = reference_price(amount, &curve, discount_bps);
+            prop_assert!(fixed.abs_diff(float) <= 1, "curve={:?} amount={} fixed={} float={}", curve, amount, fixed, float);
+        }
+
+        #[test]
+        fn discounted_price_never_below_exact(base_price in 0u128..u64::MAX as u128 * PRICE_FRAC, discount_bps in any::<u16>()) {
+            let multiplier = BPS - (discount_bps as u128).min(BPS);
+            let exact_numerator = base_price * multiplier;
+            let divisor = PRICE_FRAC * BPS;
+            let price = apply_discount(base_price, discount_bps);
+            // price >= exact, and less than one unit above it
+            prop_assert!(price * divisor >= exact_numerator);
+            prop_assert!(price * divisor < exact_numerator + divisor);
+        }
+
+        #[test]
+        fn price_stays_within_bounds(curve in arb_curve(), amount in any::<u64>(), decimals in 0u8..=18, discount_bps in any::<u16>()) {
+            let price = price_per_token(amount, decimals, &curve, discount_bps).unwrap();
+            prop_assert!(price >= curve.price_min && price <= curve.price_max);
+        }
+
+        #[test]
+        fn price_never_increases_with_amount(curve in arb_curve(), amount in 0u64..u64::MAX - 1_000, step in 1u64..1_000, decimals in 0u8..=9, discount_bps in 0u16..10_000) {
+            let smaller = price_per_token(amount, decimals, &curve, discount_bps).unwrap();
+            let larger = price_per_token(amount + step, decimals, &curve, discount_bps).unwrap();
+            prop_assert!(larger <= smaller);
+        }
+
+        #[test]
+        fn whole_tokens_price_the_same_in_base_units(curve in arb_curve(), amount in 0u64..10_000_000, decimals in 0u8..=9, discount_bps in 0u16..10_000) {
+            let base_units = amount * 10u64.pow(decimals as u32);
+            prop_assert_eq!(
+                price_per_token(base_units, decimals, &curve, discount_bps).unwrap(),
+                price_per_token(amount, 0, &curve, discount_bps).unwrap()
+            );
+        }
+    }
+}
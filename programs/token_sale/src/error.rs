@@ -38,8 +38,135 @@ pub enum ErrorCode {
     PythAccountNotFound,
     #[msg("Invalid Pyth account")]
     InvalidPythAccount,
+    // Deprecated: the fixed USDC/EURC buy paths are gone. Kept so later error codes keep their numbers.
     #[msg("Invalid USDC mint address")]
     InvalidUsdcMint,
     #[msg("Invalid EURC mint address")]
     InvalidEurcMint,
-}
\ No newline at end of file
+    #[msg("Invalid oracle limits")]
+    InvalidOracleLimits,
+    #[msg("Price update is not fully verified")]
+    InsufficientVerification,
+    #[msg("Oracle feeds not configured")]
+    OracleNotConfigured,
+    #[msg("Sale has not started")]
+    SaleNotStarted,
+    #[msg("No round is active")]
+    NoActiveRound,
+    #[msg("Invalid round parameters")]
+    InvalidRound,
+    #[msg("Round overlaps another round")]
+    RoundOverlap,
+    #[msg("Round has already started")]
+    RoundAlreadyStarted,
+    #[msg("Round not found")]
+    RoundNotFound,
+    #[msg("Too many rounds")]
+    TooManyRounds,
+    #[msg("Invalid pricing parameters")]
+    InvalidPricingParams,
+    #[msg("Total payment exceeds the buyer's maximum")]
+    SlippageExceeded,
+    #[msg("No vested tokens to claim")]
+    NothingToClaim,
+    #[msg("Purchase is below the round minimum")]
+    BelowMinimumPurchase,
+    #[msg("Purchase exceeds the per-wallet limit for this round")]
+    WalletLimitExceeded,
+    #[msg("Wallet is not on the round allowlist")]
+    NotAllowlisted,
+    #[msg("Purchase exceeds the wallet's allowlist allocation")]
+    AllowlistAllocationExceeded,
+    #[msg("Invalid KYC configuration")]
+    InvalidKycConfig,
+    #[msg("KYC attestation required")]
+    KycRequired,
+    #[msg("Invalid KYC attestation")]
+    InvalidKycAttestation,
+    #[msg("KYC attestation expired")]
+    KycAttestationExpired,
+    #[msg("Buyer's jurisdiction is blocked")]
+    JurisdictionBlocked,
+    #[msg("Sale hard cap reached")]
+    HardCapReached,
+    #[msg("Invalid sale caps")]
+    InvalidSaleCaps,
+    #[msg("Sale caps can't change after tokens were sold")]
+    CapsLocked,
+    #[msg("Vault token account required")]
+    VaultRequired,
+    #[msg("Sale is not in escrow mode")]
+    EscrowDisabled,
+    #[msg("Sale has not ended")]
+    SaleNotEnded,
+    #[msg("Soft cap reached - refunds are unavailable")]
+    SoftCapReached,
+    #[msg("Soft cap not reached")]
+    SoftCapNotReached,
+    #[msg("Sale already finalized")]
+    AlreadyFinalized,
+    #[msg("Nothing to refund")]
+    NothingToRefund,
+    #[msg("Vault funds are locked until the sale is finalized")]
+    FundsLocked,
+    #[msg("Insufficient vault balance")]
+    InsufficientVaultBalance,
+    #[msg("Invalid revenue split")]
+    InvalidRevenueSplit,
+    #[msg("Revenue split recipient accounts don't match the split")]
+    InvalidRecipientAccount,
+    #[msg("Invalid referral configuration")]
+    InvalidReferralConfig,
+    #[msg("Referral rewards can't be used with escrow")]
+    ReferralEscrowConflict,
+    #[msg("Only existing buyers can become referrers")]
+    ReferrerNotEligible,
+    #[msg("Buyer can't refer themselves")]
+    SelfReferral,
+    #[msg("Referral would create a loop")]
+    ReferralLoop,
+    #[msg("Referrer can only be set on the first purchase")]
+    ReferrerLocked,
+    #[msg("Referral accounts required for this buyer")]
+    ReferrerRequired,
+    #[msg("Referral accounts don't match the buyer's referrer")]
+    ReferrerMismatch,
+    #[msg("Payment currency is not accepted")]
+    CurrencyNotAccepted,
+    #[msg("Payment currency is disabled")]
+    CurrencyDisabled,
+    #[msg("Payment currency already registered")]
+    CurrencyAlreadyRegistered,
+    #[msg("Too many payment currencies")]
+    TooManyCurrencies,
+    #[msg("Airdrop leaf already claimed")]
+    AlreadyClaimed,
+    #[msg("Claim index is outside this bitmap chunk")]
+    InvalidClaimIndex,
+    #[msg("Airdrop root cannot change after claims have started")]
+    AirdropRootLocked,
+    #[msg("Claim amount exceeds the per-claim airdrop maximum")]
+    AirdropAmountExceeded,
+    #[msg("Airdrop token budget exceeded")]
+    AirdropBudgetExceeded,
+    #[msg("Unsupported airdrop leaf version")]
+    InvalidLeafVersion,
+    #[msg("Bonus-token referral rewards can't be used with vesting rounds")]
+    ReferralVestingConflict,
+    #[msg("Vesting account required for purchases in vesting rounds")]
+    VestingAccountRequired,
+    #[msg("Refund the sale tokens before token payments")]
+    TokensNotRefunded,
+    #[msg("Rounds can't change once an escrowed sale has ended")]
+    EscrowSaleEnded,
+}
+
+// Asserts that `result` failed with `expected`. ErrorCode has no PartialEq, so this
+// compares error numbers.
+#[cfg(test)]
+pub fn assert_error<T: std::fmt::Debug>(result: Result<T>, expected: ErrorCode) {
+    match result.unwrap_err() {
+        Error::AnchorError(error) => assert_eq!(error.error_code_number, u32::from(expected)),
+        error => panic!("unexpected error {error:?}"),
+    }
+}
@@ -1,5 +1,8 @@
 use anchor_lang::prelude::*;
-use crate::state::Config;
+use crate::state::{
+    Config, KycConfig, OracleConfig, SaleCaps, OracleKind, DEFAULT_MAX_PRICE_AGE, DEFAULT_MAX_CONFIDENCE_BPS,
+    MAX_BLOCKED_JURISDICTIONS, ReferralConfig, ReferralRewardKind, MAX_REFERRAL_REWARD_BPS, RoundSchedule,
+};
 use crate::error::ErrorCode;
 
 #[derive(Accounts)]
@@ -39,6 +42,22 @@ pub struct OnlyOwner<'info> {
     pub payer: Signer<'info>,
 }
 
+#[derive(Accounts)]
+pub struct SetReferralConfig<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"round_schedule"], bump = round_schedule.bump)]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    pub payer: Signer<'info>,
+}
+
 
 pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     let config = &mut ctx.accounts.config;
@@ -48,19 +67,29 @@ pub fn initialize(ctx: Context<Initialize>) -> Result<()> {
     config.paused = false;
     config.initialized = true;
     config.bump = ctx.bumps.config;
-    config.sale_start_time = Clock::get()?.unix_timestamp;
-    
-    // Initialize round counters
-    config.round1_tokens_sold = 0;
-    config.round2_tokens_sold = 0;
-    config.round3_tokens_sold = 0;
-
-    msg!("✅ Token sale initialized with 3 rounds (90 days total)");
-    msg!("🎯 Round 1: 30 days - 10% discount - 1M tokens");
-    msg!("🎯 Round 2: 30 days - 5% discount - 1M tokens"); 
-    msg!("🎯 Round 3: 30 days - Base price - 1M tokens");
-    msg!("💰 Payment methods: SOL, EURC, USDC");
-    msg!("💰 Total sale: 3M tokens");
+
+    // Oracle feeds are set later with set_oracle_feeds (they differ per cluster)
+    config.oracle = OracleConfig {
+        kind: OracleKind::Legacy,
+        eur_usd_feed: Pubkey::default(),
+        sol_usd_feed: Pubkey::default(),
+        eur_usd_feed_id: [0u8; 32],
+        sol_usd_feed_id: [0u8; 32],
+        max_price_age: DEFAULT_MAX_PRICE_AGE,
+        max_confidence_bps: DEFAULT_MAX_CONFIDENCE_BPS,
+    };
+
+    // KYC is off until enabled with set_kyc_config
+    config.kyc = KycConfig::default();
+
+    // No escrow and no sale-wide caps until set_sale_caps
+    config.caps = SaleCaps::default();
+
+    // Referrals are off until enabled with set_referral_config
+    config.referral = ReferralConfig::default();
+
+    msg!("✅ Token sale initialized - configure rounds with add_round");
+    msg!("💰 Payment methods: SOL, plus currencies registered with add_payment_currency");
     Ok(())
 }
 
@@ -74,17 +103,128 @@ pub fn update_mint(ctx: Context<OnlyOwner>, new_mint: Pubkey) -> Result<()> {
     msg!("Mint updated to: {}", new_mint);
     Ok(())
 }
-pub fn update_sale_start_time(ctx: Context<OnlyOwner>) -> Result<()> {
+
+pub fn set_oracle_feeds(ctx: Context<OnlyOwner>, eur_usd_feed: Pubkey, sol_usd_feed: Pubkey) -> Result<()> {
+    require!(
+        eur_usd_feed != Pubkey::default() && sol_usd_feed != Pubkey::default(),
+        ErrorCode::InvalidPythAccount
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.oracle.eur_usd_feed = eur_usd_feed;
+    config.oracle.sol_usd_feed = sol_usd_feed;
+
+    msg!("Oracle feeds updated: EUR/USD={}, SOL/USD={}", eur_usd_feed, sol_usd_feed);
+    Ok(())
+}
+
+pub fn set_pull_oracle_feeds(
+    ctx: Context<OnlyOwner>,
+    eur_usd_feed_id: [u8; 32],
+    sol_usd_feed_id: [u8; 32],
+) -> Result<()> {
+    require!(
+        eur_usd_feed_id != [0u8; 32] && sol_usd_feed_id != [0u8; 32],
+        ErrorCode::InvalidPythAccount
+    );
+
     let config = &mut ctx.accounts.config;
-    let current_time = Clock::get()?.unix_timestamp;
-    
-    config.sale_start_time = current_time;
-    
-    // Optionally reset token counters if you want to restart the sale
-    config.round1_tokens_sold = 0;
-    config.round2_tokens_sold = 0;
-    config.round3_tokens_sold = 0;
-    
-    msg!("Sale start time updated to current time: {}", current_time);
+    config.oracle.eur_usd_feed_id = eur_usd_feed_id;
+    config.oracle.sol_usd_feed_id = sol_usd_feed_id;
+
+    msg!("Pull oracle feed IDs updated");
     Ok(())
-}
\ No newline at end of file
+}
+
+pub fn set_oracle_kind(ctx: Context<OnlyOwner>, kind: OracleKind) -> Result<()> {
+    let config = &mut ctx.accounts.config;
+
+    // Don't switch to a backend whose feeds haven't been set yet
+    let configured = match kind {
+        OracleKind::Legacy => {
+            config.oracle.eur_usd_feed != Pubkey::default()
+                && config.oracle.sol_usd_feed != Pubkey::default()
+        }
+        OracleKind::Pull => {
+            config.oracle.eur_usd_feed_id != [0u8; 32]
+                && config.oracle.sol_usd_feed_id != [0u8; 32]
+        }
+    };
+    require!(configured, ErrorCode::OracleNotConfigured);
+
+    config.oracle.kind = kind;
+    msg!("Oracle backend set to: {:?}", kind);
+    Ok(())
+}
+
+pub fn set_oracle_limits(ctx: Context<OnlyOwner>, max_price_age: u64, max_confidence_bps: u16) -> Result<()> {
+    require!(max_price_age > 0, ErrorCode::InvalidOracleLimits);
+    require!(
+        max_confidence_bps > 0 && max_confidence_bps <= 10_000,
+        ErrorCode::InvalidOracleLimits
+    );
+
+    let config = &mut ctx.accounts.config;
+    config.oracle.max_price_age = max_price_age;
+    config.oracle.max_confidence_bps = max_confidence_bps;
+
+    msg!("Oracle limits updated: max_age={}s, max_confidence={}bps", max_price_age, max_confidence_bps);
+    Ok(())
+}
+
+// Turn KYC gating on or off and set the attestation signer
+pub fn set_kyc_config(ctx: Context<OnlyOwner>, required: bool, authority: Pubkey) -> Result<()> {
+    require!(!required || authority != Pubkey::default(), ErrorCode::InvalidKycConfig);
+
+    let config = &mut ctx.accounts.config;
+    config.kyc.required = required;
+    config.kyc.authority = authority;
+
+    msg!("KYC {}: authority={}", if required { "required" } else { "disabled" }, authority);
+    Ok(())
+}
+
+// Replace the jurisdiction blocklist (ISO 3166-1 alpha-2 codes, e.g. *b"US")
+pub fn set_blocked_jurisdictions(ctx: Context<OnlyOwner>, jurisdictions: Vec<[u8; 2]>) -> Result<()> {
+    require!(
+        jurisdictions.len() <= MAX_BLOCKED_JURISDICTIONS,
+        ErrorCode::InvalidKycConfig
+    );
+    require!(
+        jurisdictions.iter().all(|code| code.iter().all(u8::is_ascii_uppercase)),
+        ErrorCode::InvalidKycConfig
+    );
+
+    let kyc = &mut ctx.accounts.config.kyc;
+    kyc.blocked_jurisdictions = [[0u8; 2]; MAX_BLOCKED_JURISDICTIONS];
+    kyc.blocked_jurisdictions[..jurisdictions.len()].copy_from_slice(&jurisdictions);
+    kyc.blocked_count = jurisdictions.len() as u8;
+
+    msg!("Blocked jurisdictions updated: {} entries", jurisdictions.len());
+    Ok(())
+}
+
+// Referral rewards can't be combined with escrow refunds: payment shares bypass the vault and
+// bonus tokens aren't burned on refund. Bonus tokens are minted at purchase, so they can't be
+// paid in vesting rounds either.
+pub fn set_referral_config(
+    ctx: Context<SetReferralConfig>,
+    enabled: bool,
+    reward_bps: u16,
+    reward_kind: ReferralRewardKind,
+) -> Result<()> {
+    require!(reward_bps <= MAX_REFERRAL_REWARD_BPS, ErrorCode::InvalidReferralConfig);
+
+    let config = &mut ctx.accounts.config;
+    require!(!(enabled && config.caps.escrow), ErrorCode::ReferralEscrowConflict);
+    require!(
+        !(enabled
+            && reward_kind == ReferralRewardKind::BonusTokens
+            && ctx.accounts.round_schedule.rounds.iter().any(|round| round.has_vesting())),
+        ErrorCode::ReferralVestingConflict
+    );
+    config.referral = ReferralConfig { enabled, reward_bps, reward_kind };
+
+    msg!("Referrals {}: {}bps as {:?}", if enabled { "enabled" } else { "disabled" }, reward_bps, reward_kind);
+    Ok(())
+}
@@ -7,24 +7,77 @@ use sha2::{Digest, Sha256};
 use crate::error::ErrorCode;
 use crate::state::config::Config;
 
+// One airdrop campaign, seeded by its campaign ID so several can run at once
 #[account]
 #[derive(InitSpace)]
 pub struct AirdropConfig {
+    pub campaign_id: u64,
     pub merkle_root: [u8; 32],
+    // Largest amount a single leaf may claim, in whole tokens
     pub airdrop_amount: u64,
     pub total_claimed: u64,
     pub max_claims: u64,
-    #[max_len(50)]
-    pub claimed: Vec<Pubkey>,
     pub airdrop_start_time: i64,
     pub airdrop_end_time: i64,
     pub bump: u8,
+    // Total whole tokens this campaign may mint across all claims
+    pub total_token_budget: u64,
+    pub total_tokens_claimed: u64,
+    pub leaf_version: u8,
 }
 
+// Leaf formats accepted by set_merkle_root and set_round_allowlist. Legacy roots hash raw
+// leaves and nodes; v1 tags leaves with 0x00 and nodes with 0x01 so an internal node can never
+// be passed off as a leaf, and binds each leaf to this program (and airdrop campaign).
+pub const LEAF_VERSION_LEGACY: u8 = 0;
+pub const LEAF_VERSION_V1: u8 = 1;
+
+const LEAF_PREFIX: u8 = 0x00;
+const NODE_PREFIX: u8 = 0x01;
+
+// Claims are tracked per campaign by leaf index in fixed-size bitmap chunks, created by the
+// owner with init_claim_bitmap. Chunk `n` covers indexes [n * CLAIMS_PER_BITMAP, (n + 1) * CLAIMS_PER_BITMAP).
+pub const CLAIM_BITMAP_WORDS: usize = 1024;
+pub const CLAIMS_PER_BITMAP: u64 = (CLAIM_BITMAP_WORDS * 64) as u64;
+
+#[account(zero_copy)]
+pub struct ClaimBitmap {
+    pub campaign_id: u64,
+    pub chunk_index: u32,
+    pub bump: u8,
+    pub _padding: [u8; 3],
+    pub words: [u64; CLAIM_BITMAP_WORDS],
+}
+
+impl ClaimBitmap {
+    pub fn chunk_for(index: u64) -> u64 {
+        index / CLAIMS_PER_BITMAP
+    }
+
+    fn position(index: u64) -> (usize, u64) {
+        let offset = index % CLAIMS_PER_BITMAP;
+        ((offset / 64) as usize, 1u64 << (offset % 64))
+    }
+
+    pub fn is_claimed(&self, index: u64) -> bool {
+        let (word, mask) = Self::position(index);
+        self.words[word] & mask != 0
+    }
+
+    pub fn set_claimed(&mut self, index: u64) {
+        let (word, mask) = Self::position(index);
+        self.words[word] |= mask;
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
 pub fn set_merkle_root_handler(
     ctx: Context<SetMerkleRoot>, 
+    campaign_id: u64,
     root: [u8; 32],
+    leaf_version: u8,
     airdrop_amount: u64,
+    total_token_budget: u64,
     max_claims: u64,
     start_time: i64,
     end_time: i64,
@@ -34,8 +87,29 @@ pub fn set_merkle_root_handler(
     // Check if this is a new config or update
     let is_new_config = config.merkle_root == [0u8; 32]; // Simple check
 
+    require!(leaf_version <= LEAF_VERSION_V1, ErrorCode::InvalidLeafVersion);
+
+    // Claimed bits refer to leaves of the current root, so it is fixed after the first claim
+    require!(
+        config.total_claimed == 0 || (config.merkle_root == root && config.leaf_version == leaf_version),
+        ErrorCode::AirdropRootLocked
+    );
+
+    // The budget may be lowered, but never below what has already been minted
+    require!(
+        airdrop_amount > 0 && airdrop_amount <= total_token_budget,
+        ErrorCode::InvalidAmount
+    );
+    require!(
+        total_token_budget >= config.total_tokens_claimed,
+        ErrorCode::AirdropBudgetExceeded
+    );
+
+    config.campaign_id = campaign_id;
     config.merkle_root = root;
+    config.leaf_version = leaf_version;
     config.airdrop_amount = airdrop_amount;
+    config.total_token_budget = total_token_budget;
     config.max_claims = max_claims;
     config.airdrop_start_time = start_time;
     config.airdrop_end_time = end_time;
@@ -43,22 +117,37 @@ pub fn set_merkle_root_handler(
     if is_new_config {
         // Initialize for new airdrop
         config.total_claimed = 0;
-        config.claimed = Vec::new();
+        config.total_tokens_claimed = 0;
         config.bump = ctx.bumps.config;
-        msg!("New airdrop configured");
+        msg!("New airdrop campaign {} configured", campaign_id);
     } else {
-        // Keep existing claimed data for updates
-        // Or reset if you want fresh start:
-        // config.total_claimed = 0;
-        // config.claimed.clear();
-        msg!("Existing airdrop updated");
+        // Claim bitmaps are kept across updates
+        msg!("Airdrop campaign {} updated", campaign_id);
     }
 
-    msg!("Airdrop: amount={}, max_claims={}", airdrop_amount, max_claims);
+    msg!("Airdrop: leaf_version={}, max_per_claim={}, budget={}, max_claims={}",
+         leaf_version, airdrop_amount, total_token_budget, max_claims);
     Ok(())
 }
 
-pub fn claim_handler(ctx: Context<Claim>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
+pub fn init_claim_bitmap_handler(ctx: Context<InitClaimBitmap>, campaign_id: u64, chunk_index: u32) -> Result<()> {
+    let mut bitmap = ctx.accounts.claim_bitmap.load_init()?;
+    bitmap.campaign_id = campaign_id;
+    bitmap.chunk_index = chunk_index;
+    bitmap.bump = ctx.bumps.claim_bitmap;
+
+    msg!("Campaign {} claim bitmap {} ready for leaf indexes {}..{}",
+         campaign_id, chunk_index, chunk_index as u64 * CLAIMS_PER_BITMAP, (chunk_index as u64 + 1) * CLAIMS_PER_BITMAP);
+    Ok(())
+}
+
+pub fn claim_handler(
+    ctx: Context<Claim>,
+    campaign_id: u64,
+    index: u64,
+    amount: u64,
+    proof: Vec<[u8; 32]>,
+) -> Result<()> {
     let claimer = ctx.accounts.claimer.key();
     let current_time = Clock::get()?.unix_timestamp;
 
@@ -75,10 +164,24 @@ pub fn claim_handler(ctx: Context<Claim>, amount: u64, proof: Vec<[u8; 32]>) ->
         ErrorCode::RoundLimitExceeded
     );
 
-    // Verify merkle proof
+    // Check per-claim maximum and the campaign token budget
+    require!(
+        amount > 0 && amount <= ctx.accounts.airdrop_config.airdrop_amount,
+        ErrorCode::AirdropAmountExceeded
+    );
+    let total_tokens_claimed = ctx.accounts.airdrop_config.total_tokens_claimed
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(
+        total_tokens_claimed <= ctx.accounts.airdrop_config.total_token_budget,
+        ErrorCode::AirdropBudgetExceeded
+    );
+
+    // Verify merkle proof - the leaf commits to its index
     require!(
-        verify_merkle_proof(
-            &ctx.accounts.airdrop_config.merkle_root, 
+        verify_airdrop_proof(
+            &ctx.accounts.airdrop_config, 
+            index,
             &claimer, 
             amount, 
             &proof
@@ -86,20 +189,24 @@ pub fn claim_handler(ctx: Context<Claim>, amount: u64, proof: Vec<[u8; 32]>) ->
         ErrorCode::Unauthorized
     );
 
-    // Prevent double-claim
+    // Prevent double-claim - one bit per leaf index
+    let mut bitmap = ctx.accounts.claim_bitmap.load_mut()?;
     require!(
-        !ctx.accounts.airdrop_config.claimed.contains(&claimer),
-        ErrorCode::Unauthorized
+        ClaimBitmap::chunk_for(index) == bitmap.chunk_index as u64,
+        ErrorCode::InvalidClaimIndex
     );
+    require!(!bitmap.is_claimed(index), ErrorCode::AlreadyClaimed);
 
-    // Add to claimed list and update counters
-    ctx.accounts.airdrop_config.claimed.push(claimer);
+    // Mark claimed and update counters
+    bitmap.set_claimed(index);
+    drop(bitmap);
     ctx.accounts.airdrop_config.total_claimed += 1;
+    ctx.accounts.airdrop_config.total_tokens_claimed = total_tokens_claimed;
 
     // Adjust decimals
     let decimals = ctx.accounts.mint.decimals;
-    let factor = 10u64.checked_pow(decimals as u32).unwrap();
-    let base_units = amount.checked_mul(factor).unwrap();
+    let factor = 10u64.checked_pow(decimals as u32).ok_or(ErrorCode::Overflow)?;
+    let base_units = amount.checked_mul(factor).ok_or(ErrorCode::Overflow)?;
 
     // Mint tokens
     let seeds = b"mint"; // Make sure this matches your sale.rs
@@ -122,31 +229,70 @@ pub fn claim_handler(ctx: Context<Claim>, amount: u64, proof: Vec<[u8; 32]>) ->
     
     // Emit claim event
     emit!(ClaimEvent {
+        campaign_id,
         claimer,
         amount: base_units,
         timestamp: current_time,
         merkle_root: ctx.accounts.airdrop_config.merkle_root,
     });
     
-    msg!("Airdrop claimed by: {}, amount: {}", claimer, base_units);
+    msg!("Airdrop campaign {} claimed by: {}, index: {}, amount: {}", campaign_id, claimer, index, base_units);
     Ok(())
 }
 
-fn verify_merkle_proof(
+// Allowlist leaves, amounts as u64 LE:
+//   legacy: hash(wallet || amount)
+//   v1:     hash(0x00 || program_id || wallet || amount)
+pub(crate) fn verify_merkle_proof(
     root: &[u8; 32],
+    leaf_version: u8,
     claimer: &Pubkey,
     amount: u64,
-    proof: &Vec<[u8; 32]>
+    proof: &[[u8; 32]]
 ) -> bool {
-    // Create leaf node: hash(claimer_pubkey || amount)
+    let domain_separated = leaf_version == LEAF_VERSION_V1;
+
     let mut hasher = Sha256::new();
+    if domain_separated {
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(crate::ID.as_ref());
+    }
     hasher.update(claimer.as_ref());
-    hasher.update(&amount.to_le_bytes());
-    let mut leaf = hasher.finalize().to_vec();
-    
+    hasher.update(amount.to_le_bytes());
+    verify_proof(root, hasher.finalize().to_vec(), proof, domain_separated)
+}
+
+// Airdrop leaves, index and amounts as u64 LE:
+//   legacy: hash(index || claimer_pubkey || amount)
+//   v1:     hash(0x00 || program_id || campaign_id || index || claimer_pubkey || amount)
+pub(crate) fn verify_airdrop_proof(
+    config: &AirdropConfig,
+    index: u64,
+    claimer: &Pubkey,
+    amount: u64,
+    proof: &[[u8; 32]]
+) -> bool {
+    let domain_separated = config.leaf_version == LEAF_VERSION_V1;
+
+    let mut hasher = Sha256::new();
+    if domain_separated {
+        hasher.update([LEAF_PREFIX]);
+        hasher.update(crate::ID.as_ref());
+        hasher.update(config.campaign_id.to_le_bytes());
+    }
+    hasher.update(index.to_le_bytes());
+    hasher.update(claimer.as_ref());
+    hasher.update(amount.to_le_bytes());
+    verify_proof(&config.merkle_root, hasher.finalize().to_vec(), proof, domain_separated)
+}
+
+fn verify_proof(root: &[u8; 32], mut leaf: Vec<u8>, proof: &[[u8; 32]], domain_separated: bool) -> bool {
     // Verify proof
     for proof_item in proof {
         let mut hasher = Sha256::new();
+        if domain_separated {
+            hasher.update([NODE_PREFIX]);
+        }
         
         // Compare to determine order (left or right)
         if leaf.as_slice() < proof_item {
@@ -166,6 +312,7 @@ fn verify_merkle_proof(
 
 #[event]
 pub struct ClaimEvent {
+    pub campaign_id: u64,
     pub claimer: Pubkey,
     pub amount: u64,
     pub timestamp: i64,
@@ -174,12 +321,15 @@ pub struct ClaimEvent {
 
 // Context structs for airdrop
 #[derive(Accounts)]
+#[instruction(campaign_id: u64)]
 pub struct SetMerkleRoot<'info> {
     #[account(mut)]
     pub authority: Signer<'info>,
     
     #[account(
-        constraint = sale_config.owner == authority.key() @ ErrorCode::Unauthorized, // Fixed constraint
+        seeds = [b"token_sale_config"],
+        bump = sale_config.bump,
+        constraint = sale_config.owner == authority.key() @ ErrorCode::Unauthorized,
     )]
     pub sale_config: Account<'info, Config>,
     
@@ -187,7 +337,7 @@ pub struct SetMerkleRoot<'info> {
         init_if_needed,
         payer = authority,
         space = 8 + AirdropConfig::INIT_SPACE,
-        seeds = [b"airdrop-config"],
+        seeds = [b"airdrop-config", campaign_id.to_le_bytes().as_ref()],
         bump
     )]
     pub config: Account<'info, AirdropConfig>,
@@ -196,18 +346,71 @@ pub struct SetMerkleRoot<'info> {
 }
 
 #[derive(Accounts)]
+#[instruction(campaign_id: u64, chunk_index: u32)]
+pub struct InitClaimBitmap<'info> {
+    #[account(mut)]
+    pub authority: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = sale_config.bump,
+        constraint = sale_config.owner == authority.key() @ ErrorCode::Unauthorized,
+    )]
+    pub sale_config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"airdrop-config", campaign_id.to_le_bytes().as_ref()],
+        bump = airdrop_config.bump
+    )]
+    pub airdrop_config: Account<'info, AirdropConfig>,
+
+    #[account(
+        init,
+        payer = authority,
+        space = 8 + std::mem::size_of::<ClaimBitmap>(),
+        seeds = [b"claim-bitmap", campaign_id.to_le_bytes().as_ref(), chunk_index.to_le_bytes().as_ref()],
+        bump
+    )]
+    pub claim_bitmap: AccountLoader<'info, ClaimBitmap>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+#[instruction(campaign_id: u64)]
 pub struct Claim<'info> {
     #[account(mut)]
     pub claimer: Signer<'info>,
+
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = sale_config.bump
+    )]
+    pub sale_config: Account<'info, Config>,
     
     #[account(
         mut,
-        seeds = [b"airdrop-config"],
+        seeds = [b"airdrop-config", campaign_id.to_le_bytes().as_ref()],
         bump = airdrop_config.bump
     )]
     pub airdrop_config: Account<'info, AirdropConfig>,
+
+    // Campaign bitmap chunk covering the claimed leaf index
+    #[account(
+        mut,
+        seeds = [
+            b"claim-bitmap",
+            campaign_id.to_le_bytes().as_ref(),
+            claim_bitmap.load()?.chunk_index.to_le_bytes().as_ref()
+        ],
+        bump = claim_bitmap.load()?.bump
+    )]
+    pub claim_bitmap: AccountLoader<'info, ClaimBitmap>,
     
-    #[account(mut)]
+    #[account(
+        mut,
+        address = sale_config.mint @ ErrorCode::InvalidMint
+    )]
     pub mint: InterfaceAccount<'info, Mint>,
     
     /// CHECK: We'll initialize this ATA if needed
@@ -234,40 +437,205 @@ pub struct Claim<'info> {
 
 // Utility functions
 impl AirdropConfig {
-    pub fn can_claim(&self, claimer: &Pubkey, amount: u64, proof: &Vec<[u8; 32]>) -> bool {
+    pub fn can_claim(
+        &self,
+        bitmap: &ClaimBitmap,
+        index: u64,
+        claimer: &Pubkey,
+        amount: u64,
+        proof: &[[u8; 32]],
+    ) -> bool {
         let current_time = Clock::get().unwrap().unix_timestamp;
         
         current_time >= self.airdrop_start_time && 
         current_time <= self.airdrop_end_time &&
-        !self.claimed.contains(claimer) && 
+        ClaimBitmap::chunk_for(index) == bitmap.chunk_index as u64 &&
+        !bitmap.is_claimed(index) && 
         self.total_claimed < self.max_claims &&
-        verify_merkle_proof(&self.merkle_root, claimer, amount, proof)
+        amount > 0 &&
+        amount <= self.airdrop_amount &&
+        amount <= self.get_remaining_tokens() &&
+        verify_airdrop_proof(self, index, claimer, amount, proof)
     }
 
     pub fn get_remaining_claims(&self) -> u64 {
         self.max_claims.saturating_sub(self.total_claimed)
     }
 
+    pub fn get_remaining_tokens(&self) -> u64 {
+        self.total_token_budget.saturating_sub(self.total_tokens_claimed)
+    }
+
     pub fn get_airdrop_info(&self) -> AirdropInfo {
         AirdropInfo {
+            campaign_id: self.campaign_id,
             merkle_root: self.merkle_root,
+            leaf_version: self.leaf_version,
             airdrop_amount: self.airdrop_amount,
             total_claimed: self.total_claimed,
             max_claims: self.max_claims,
             airdrop_start_time: self.airdrop_start_time,
             airdrop_end_time: self.airdrop_end_time,
             remaining_claims: self.get_remaining_claims(),
+            total_token_budget: self.total_token_budget,
+            total_tokens_claimed: self.total_tokens_claimed,
+            remaining_tokens: self.get_remaining_tokens(),
         }
     }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct AirdropInfo {
+    pub campaign_id: u64,
     pub merkle_root: [u8; 32],
+    pub leaf_version: u8,
     pub airdrop_amount: u64,
     pub total_claimed: u64,
     pub max_claims: u64,
     pub airdrop_start_time: i64,
     pub airdrop_end_time: i64,
     pub remaining_claims: u64,
-}
\ No newline at end of file
+    pub total_token_budget: u64,
+    pub total_tokens_claimed: u64,
+    pub remaining_tokens: u64,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sha(parts: &[&[u8]]) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        for part in parts {
+            hasher.update(part);
+        }
+        hasher.finalize().into()
+    }
+
+    fn node(a: [u8; 32], b: [u8; 32], domain_separated: bool) -> [u8; 32] {
+        let (left, right) = if a < b { (a, b) } else { (b, a) };
+        if domain_separated {
+            sha(&[&[NODE_PREFIX], &left, &right])
+        } else {
+            sha(&[&left, &right])
+        }
+    }
+
+    fn airdrop_leaf(version: u8, campaign_id: u64, index: u64, claimer: &Pubkey, amount: u64) -> [u8; 32] {
+        let body = [index.to_le_bytes().as_ref(), claimer.as_ref(), amount.to_le_bytes().as_ref()].concat();
+        if version == LEAF_VERSION_V1 {
+            sha(&[&[LEAF_PREFIX], crate::ID.as_ref(), &campaign_id.to_le_bytes(), &body])
+        } else {
+            sha(&[&body])
+        }
+    }
+
+    fn allowlist_leaf(version: u8, wallet: &Pubkey, allocation: u64) -> [u8; 32] {
+        let body = [wallet.as_ref(), allocation.to_le_bytes().as_ref()].concat();
+        if version == LEAF_VERSION_V1 {
+            sha(&[&[LEAF_PREFIX], crate::ID.as_ref(), &body])
+        } else {
+            sha(&[&body])
+        }
+    }
+
+    fn campaign(merkle_root: [u8; 32], leaf_version: u8) -> AirdropConfig {
+        AirdropConfig {
+            campaign_id: 7,
+            merkle_root,
+            airdrop_amount: 100,
+            total_claimed: 0,
+            max_claims: 10,
+            airdrop_start_time: 0,
+            airdrop_end_time: 0,
+            bump: 0,
+            total_token_budget: 1_000,
+            total_tokens_claimed: 0,
+            leaf_version,
+        }
+    }
+
+    // Four-leaf airdrop tree for campaign 7: (claimers, amounts, leaves, root)
+    fn airdrop_tree(version: u8) -> (Vec<Pubkey>, Vec<u64>, Vec<[u8; 32]>, [u8; 32]) {
+        let claimers: Vec<Pubkey> = (0..4).map(|_| Pubkey::new_unique()).collect();
+        let amounts = vec![10, 20, 30, 40];
+        let leaves: Vec<[u8; 32]> = (0..4)
+            .map(|i| airdrop_leaf(version, 7, i as u64, &claimers[i], amounts[i]))
+            .collect();
+        let domain_separated = version == LEAF_VERSION_V1;
+        let root = node(
+            node(leaves[0], leaves[1], domain_separated),
+            node(leaves[2], leaves[3], domain_separated),
+            domain_separated,
+        );
+        (claimers, amounts, leaves, root)
+    }
+
+    fn proof_for(index: usize, leaves: &[[u8; 32]], domain_separated: bool) -> Vec<[u8; 32]> {
+        let sibling = leaves[index ^ 1];
+        let other_pair = if index < 2 { (leaves[2], leaves[3]) } else { (leaves[0], leaves[1]) };
+        vec![sibling, node(other_pair.0, other_pair.1, domain_separated)]
+    }
+
+    #[test]
+    fn v1_proof_verifies() {
+        let (claimers, amounts, leaves, root) = airdrop_tree(LEAF_VERSION_V1);
+        let config = campaign(root, LEAF_VERSION_V1);
+
+        for i in 0..4 {
+            let proof = proof_for(i, &leaves, true);
+            assert!(verify_airdrop_proof(&config, i as u64, &claimers[i], amounts[i], &proof));
+            assert!(!verify_airdrop_proof(&config, i as u64, &claimers[i], amounts[i] + 1, &proof));
+        }
+
+        // Leaves are bound to their campaign
+        let other_campaign = AirdropConfig { campaign_id: 8, ..config };
+        let proof = proof_for(0, &leaves, true);
+        assert!(!verify_airdrop_proof(&other_campaign, 0, &claimers[0], amounts[0], &proof));
+    }
+
+    #[test]
+    fn v1_internal_node_is_not_a_leaf() {
+        // Present the 64-byte preimage of the (leaf 0, leaf 1) node as leaf data
+        let (_, _, leaves, root) = airdrop_tree(LEAF_VERSION_V1);
+        let (left, right) = if leaves[0] < leaves[1] { (leaves[0], leaves[1]) } else { (leaves[1], leaves[0]) };
+        let upper = node(leaves[2], leaves[3], true);
+        let forged = sha(&[&[LEAF_PREFIX], &left, &right]);
+        assert!(!verify_proof(&root, forged.to_vec(), &[upper], true));
+
+        // Without prefixes the same forgery lands on the node and verifies
+        let (_, _, leaves, root) = airdrop_tree(LEAF_VERSION_LEGACY);
+        let (left, right) = if leaves[0] < leaves[1] { (leaves[0], leaves[1]) } else { (leaves[1], leaves[0]) };
+        let upper = node(leaves[2], leaves[3], false);
+        let forged = sha(&[&left, &right]);
+        assert!(verify_proof(&root, forged.to_vec(), &[upper], false));
+    }
+
+    #[test]
+    fn legacy_root_needs_legacy_version() {
+        let (claimers, amounts, leaves, root) = airdrop_tree(LEAF_VERSION_LEGACY);
+        let proof = proof_for(1, &leaves, false);
+        assert!(verify_airdrop_proof(&campaign(root, LEAF_VERSION_LEGACY), 1, &claimers[1], amounts[1], &proof));
+        assert!(!verify_airdrop_proof(&campaign(root, LEAF_VERSION_V1), 1, &claimers[1], amounts[1], &proof));
+
+        let (claimers, amounts, leaves, root) = airdrop_tree(LEAF_VERSION_V1);
+        let proof = proof_for(1, &leaves, true);
+        assert!(!verify_airdrop_proof(&campaign(root, LEAF_VERSION_LEGACY), 1, &claimers[1], amounts[1], &proof));
+    }
+
+    #[test]
+    fn allowlist_leaf_versions() {
+        let wallets = [Pubkey::new_unique(), Pubkey::new_unique()];
+        for version in [LEAF_VERSION_LEGACY, LEAF_VERSION_V1] {
+            let domain_separated = version == LEAF_VERSION_V1;
+            let leaves = [allowlist_leaf(version, &wallets[0], 500), allowlist_leaf(version, &wallets[1], 900)];
+            let root = node(leaves[0], leaves[1], domain_separated);
+            let other_version = LEAF_VERSION_V1 - version;
+
+            assert!(verify_merkle_proof(&root, version, &wallets[0], 500, &[leaves[1]]));
+            assert!(verify_merkle_proof(&root, version, &wallets[1], 900, &[leaves[0]]));
+            assert!(!verify_merkle_proof(&root, version, &wallets[1], 901, &[leaves[0]]));
+            assert!(!verify_merkle_proof(&root, other_version, &wallets[0], 500, &[leaves[1]]));
+        }
+    }
+}
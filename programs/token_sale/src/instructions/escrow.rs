@@ -0,0 +1,771 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::get_associated_token_address_with_program_id;
+use anchor_spl::token_2022::{self as token22, Burn, Token2022};
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{
+    Config, ReferralConfig, RoundSchedule, BuyerRecord, VestingAccount, RevenueSplit, PaymentRegistry, SaleCaps, Refunded,
+    TokenRefunded, SaleFinalized, FundsWithdrawn, RevenueSharePaid, MAX_ROUNDS,
+};
+use crate::error::ErrorCode;
+use crate::token_transfer;
+
+// Tops the SOL vault up to rent exemption. Must run before the sale opens - every SOL
+// buy pays into the vault. Token vault accounts are created by add_payment_currency.
+#[derive(Accounts)]
+pub struct InitializeVaults<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetSaleCaps<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"round_schedule"], bump = round_schedule.bump)]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Refund<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.initialized @ ErrorCode::NotInitialized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"round_schedule"], bump = round_schedule.bump)]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_record", buyer.key().as_ref()],
+        bump = buyer_record.bump,
+        has_one = buyer @ ErrorCode::Unauthorized
+    )]
+    pub buyer_record: Account<'info, BuyerRecord>,
+
+    // Required if the buyer bought in a vesting round
+    #[account(
+        mut,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump = vesting.bump,
+        has_one = buyer @ ErrorCode::Unauthorized
+    )]
+    pub vesting: Option<Account<'info, VestingAccount>>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, address = config.mint @ ErrorCode::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    #[account(
+        mut,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_2022_program,
+    )]
+    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub system_program: Program<'info, System>,
+}
+
+// Returns what the buyer paid in one registered currency; call once per currency
+#[derive(Accounts)]
+pub struct RefundToken<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.initialized @ ErrorCode::NotInitialized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"round_schedule"], bump = round_schedule.bump)]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    #[account(seeds = [b"payment_registry"], bump = payment_registry.bump)]
+    pub payment_registry: Account<'info, PaymentRegistry>,
+
+    #[account(
+        mut,
+        seeds = [b"buyer_record", buyer.key().as_ref()],
+        bump = buyer_record.bump,
+        has_one = buyer @ ErrorCode::Unauthorized
+    )]
+    pub buyer_record: Account<'info, BuyerRecord>,
+
+    pub buyer: Signer<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_payment_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_program,
+    )]
+    pub buyer_payment_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+#[derive(Accounts)]
+pub struct Finalize<'info> {
+    #[account(
+        mut,
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(seeds = [b"round_schedule"], bump = round_schedule.bump)]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    pub payer: Signer<'info>,
+}
+
+#[derive(Accounts)]
+pub struct Withdraw<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub payer: Signer<'info>,
+
+    /// CHECK: Treasury account
+    #[account(mut, address = config.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    /// CHECK: RevenueSplit PDA - empty until set_revenue_split, in which case withdrawals go to the treasury
+    #[account(seeds = [b"revenue_split"], bump)]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct WithdrawToken<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    pub payer: Signer<'info>,
+
+    /// CHECK: Treasury account
+    #[account(address = config.treasury @ ErrorCode::InvalidTreasury)]
+    pub treasury: UncheckedAccount<'info>,
+
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_payment_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(
+        mut,
+        associated_token::mint = payment_mint,
+        associated_token::authority = treasury,
+        associated_token::token_program = token_program,
+    )]
+    pub treasury_payment_ata: InterfaceAccount<'info, TokenAccount>,
+
+    /// CHECK: RevenueSplit PDA - empty until set_revenue_split, in which case withdrawals go to the treasury
+    #[account(seeds = [b"revenue_split"], bump)]
+    pub revenue_split: UncheckedAccount<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+}
+
+pub fn initialize_vaults(ctx: Context<InitializeVaults>) -> Result<()> {
+    // Keep the SOL vault rent-exempt so refunds and sweeps never strand dust below the minimum
+    let rent_minimum = Rent::get()?.minimum_balance(0);
+    let shortfall = rent_minimum.saturating_sub(ctx.accounts.vault.lamports());
+    if shortfall > 0 {
+        let cpi_context = CpiContext::new(
+            ctx.accounts.system_program.to_account_info(),
+            anchor_lang::system_program::Transfer {
+                from: ctx.accounts.payer.to_account_info(),
+                to: ctx.accounts.vault.to_account_info(),
+            },
+        );
+        anchor_lang::system_program::transfer(cpi_context, shortfall)?;
+    }
+
+    msg!("🏦 SOL vault ready: {}", ctx.accounts.vault.key());
+    Ok(())
+}
+
+// Caps are fixed once the first token is sold
+fn check_sale_caps(schedule: &RoundSchedule, referral: &ReferralConfig, escrow: bool, soft_cap: u64, hard_cap: u64) -> Result<()> {
+    require!(schedule.total_tokens_sold() == 0, ErrorCode::CapsLocked);
+    require!(escrow || soft_cap == 0, ErrorCode::InvalidSaleCaps);
+    require!(hard_cap == 0 || soft_cap <= hard_cap, ErrorCode::InvalidSaleCaps);
+    require!(!(escrow && referral.enabled), ErrorCode::ReferralEscrowConflict);
+    Ok(())
+}
+
+pub fn set_sale_caps(ctx: Context<SetSaleCaps>, escrow: bool, soft_cap: u64, hard_cap: u64) -> Result<()> {
+    check_sale_caps(&ctx.accounts.round_schedule, &ctx.accounts.config.referral, escrow, soft_cap, hard_cap)?;
+
+    let caps = &mut ctx.accounts.config.caps;
+    caps.escrow = escrow;
+    caps.soft_cap = soft_cap;
+    caps.hard_cap = hard_cap;
+    caps.finalized = false;
+
+    msg!("Sale caps updated: escrow={}, soft_cap={}, hard_cap={}", escrow, soft_cap, hard_cap);
+    Ok(())
+}
+
+// Refunds open once an escrowed sale ends below its soft cap without being finalized
+fn check_refund_window(caps: &SaleCaps, schedule: &RoundSchedule, now: i64) -> Result<()> {
+    require!(caps.escrow, ErrorCode::EscrowDisabled);
+    require!(!caps.finalized, ErrorCode::AlreadyFinalized);
+    require!(now >= schedule.sale_end_time(), ErrorCode::SaleNotEnded);
+    require!(!caps.soft_cap_reached(schedule.total_tokens_sold()), ErrorCode::SoftCapReached);
+    Ok(())
+}
+
+// Whether any of the buyer's purchases were in a vesting round. Rounds can't be
+// edited or closed once started, so indexes with purchases are stable.
+fn bought_in_vesting_round(record: &BuyerRecord, schedule: &RoundSchedule) -> bool {
+    schedule.rounds.iter()
+        .zip(record.tokens_per_round)
+        .any(|(round, tokens)| tokens > 0 && round.has_vesting())
+}
+
+// Refunded tokens no longer count as sold, so they can't help reach the soft cap
+fn release_refunded_tokens(schedule: &mut RoundSchedule, record: &BuyerRecord) {
+    for (round, tokens) in schedule.rounds.iter_mut().zip(record.tokens_per_round) {
+        round.tokens_sold = round.tokens_sold.saturating_sub(tokens);
+    }
+}
+
+// Clears what `refund` returns. Token payments stay in paid_per_currency for refund_token.
+fn clear_refunded_purchases(record: &mut BuyerRecord) {
+    record.tokens_per_round = [0; MAX_ROUNDS];
+    record.total_tokens = 0;
+    record.total_paid_sol = 0;
+}
+
+// Token payments only come back once `refund` has burned the buyer's tokens
+fn token_refund_amount(record: &BuyerRecord, index: usize) -> Result<u64> {
+    require!(record.total_tokens == 0, ErrorCode::TokensNotRefunded);
+    let amount = record.paid_per_currency[index];
+    require!(amount > 0, ErrorCode::NothingToRefund);
+    Ok(amount)
+}
+
+// Soft cap missed: burn the buyer's tokens, cancel their vesting and return their SOL.
+// Token payments are returned per currency by refund_token, after this has run.
+pub fn refund(ctx: Context<Refund>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    check_refund_window(&ctx.accounts.config.caps, &ctx.accounts.round_schedule, now)?;
+
+    // Tokens already in the buyer's wallet get burned; unclaimed vesting is just dropped
+    let record = &mut ctx.accounts.buyer_record;
+    require!(
+        ctx.accounts.vesting.is_some() || !bought_in_vesting_round(record, &ctx.accounts.round_schedule),
+        ErrorCode::VestingAccountRequired
+    );
+    let tokens_unvested = ctx.accounts.vesting.as_ref().map_or(0, |vesting| vesting.total_unclaimed());
+    let tokens_burned = record.total_tokens.saturating_sub(tokens_unvested);
+    let sol_amount = record.total_paid_sol;
+    require!(record.total_tokens > 0 || sol_amount > 0, ErrorCode::NothingToRefund);
+
+    release_refunded_tokens(&mut ctx.accounts.round_schedule, record);
+    clear_refunded_purchases(record);
+    if let Some(vesting) = ctx.accounts.vesting.as_mut() {
+        vesting.entries.clear();
+    }
+
+    if tokens_burned > 0 {
+        let cpi_ctx = CpiContext::new(
+            ctx.accounts.token_2022_program.to_account_info(),
+            Burn {
+                mint: ctx.accounts.mint.to_account_info(),
+                from: ctx.accounts.buyer_ata.to_account_info(),
+                authority: ctx.accounts.buyer.to_account_info(),
+            },
+        );
+        token22::burn(cpi_ctx, tokens_burned)?;
+    }
+
+    if sol_amount > 0 {
+        transfer_sol_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.buyer.to_account_info(),
+            &ctx.accounts.system_program,
+            ctx.bumps.vault,
+            sol_amount,
+        )?;
+    }
+
+    emit!(Refunded {
+        buyer: ctx.accounts.buyer.key(),
+        tokens_burned,
+        tokens_unvested,
+        sol_amount,
+        timestamp: now,
+    });
+
+    msg!("↩️ Refunded {} lamports; burned {} base units", sol_amount, tokens_burned);
+    Ok(())
+}
+
+// Transfer-hook accounts for the payment mint are passed as remaining accounts. With a
+// transfer-fee mint the buyer receives the refund less the fee.
+pub fn refund_token<'info>(ctx: Context<'_, '_, 'info, 'info, RefundToken<'info>>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    check_refund_window(&ctx.accounts.config.caps, &ctx.accounts.round_schedule, now)?;
+
+    let payment_mint = &ctx.accounts.payment_mint;
+    let (index, _) = ctx.accounts.payment_registry.currency(&payment_mint.key())?;
+    let record = &mut ctx.accounts.buyer_record;
+    let amount = token_refund_amount(record, index)?;
+    record.paid_per_currency[index] = 0;
+
+    transfer_token_from_vault(
+        &ctx.accounts.vault_payment_ata,
+        payment_mint,
+        &ctx.accounts.buyer_payment_ata.to_account_info(),
+        &ctx.accounts.vault,
+        &ctx.accounts.token_program,
+        ctx.remaining_accounts,
+        ctx.bumps.vault,
+        amount,
+    )?;
+
+    emit!(TokenRefunded {
+        buyer: ctx.accounts.buyer.key(),
+        payment_mint: payment_mint.key(),
+        amount,
+        timestamp: now,
+    });
+
+    msg!("↩️ Refunded {} of {}", amount, payment_mint.key());
+    Ok(())
+}
+
+// Finalizing needs an escrowed sale that ended at or above its soft cap
+fn check_finalize_window(caps: &SaleCaps, schedule: &RoundSchedule, now: i64) -> Result<()> {
+    require!(caps.escrow, ErrorCode::EscrowDisabled);
+    require!(!caps.finalized, ErrorCode::AlreadyFinalized);
+    require!(now >= schedule.sale_end_time(), ErrorCode::SaleNotEnded);
+    require!(caps.soft_cap_reached(schedule.total_tokens_sold()), ErrorCode::SoftCapNotReached);
+    Ok(())
+}
+
+// Soft cap met and sale over: closes refunds and unlocks withdraw
+pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let total_tokens_sold = ctx.accounts.round_schedule.total_tokens_sold();
+    check_finalize_window(&ctx.accounts.config.caps, &ctx.accounts.round_schedule, now)?;
+
+    ctx.accounts.config.caps.finalized = true;
+
+    emit!(SaleFinalized {
+        total_tokens_sold,
+        timestamp: now,
+    });
+
+    msg!("🏁 Sale finalized with {} base units sold - vault withdrawals unlocked", total_tokens_sold);
+    Ok(())
+}
+
+// Move SOL proceeds from the vault to the treasury, or across the revenue split
+// recipients if a split is configured. Escrowed sales stay locked until finalized.
+//
+// With a split, remaining_accounts holds each recipient's writable wallet, in split order.
+pub fn withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, sol_amount: u64) -> Result<()> {
+    let caps = ctx.accounts.config.caps;
+    require!(!caps.escrow || caps.finalized, ErrorCode::FundsLocked);
+    require!(sol_amount > 0, ErrorCode::InvalidAmount);
+
+    // The SOL vault keeps its rent-exempt minimum
+    let rent_minimum = Rent::get()?.minimum_balance(0);
+    let available_sol = ctx.accounts.vault.lamports().saturating_sub(rent_minimum);
+    require!(sol_amount <= available_sol, ErrorCode::InsufficientVaultBalance);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let vault_bump = ctx.bumps.vault;
+    let split = load_revenue_split(&ctx.accounts.revenue_split)?;
+    let split_recipients = split.as_ref().map_or(0, |split| split.recipients.len() as u8);
+
+    match split {
+        None => transfer_sol_from_vault(
+            &ctx.accounts.vault,
+            &ctx.accounts.treasury.to_account_info(),
+            &ctx.accounts.system_program,
+            vault_bump,
+            sol_amount,
+        )?,
+        Some(split) => {
+            let accounts = ctx.remaining_accounts;
+            require!(accounts.len() == split.recipients.len(), ErrorCode::InvalidRecipientAccount);
+
+            for ((recipient, wallet), share) in split.recipients.iter().zip(accounts).zip(split.shares(sol_amount)) {
+                require_keys_eq!(wallet.key(), recipient.wallet, ErrorCode::InvalidRecipientAccount);
+                if share > 0 {
+                    transfer_sol_from_vault(&ctx.accounts.vault, wallet, &ctx.accounts.system_program, vault_bump, share)?;
+                }
+
+                emit!(RevenueSharePaid {
+                    recipient: recipient.wallet,
+                    bps: recipient.bps,
+                    payment_mint: None,
+                    amount: share,
+                    timestamp,
+                });
+            }
+        }
+    }
+
+    emit!(FundsWithdrawn {
+        withdrawn_by: ctx.accounts.payer.key(),
+        treasury: ctx.accounts.treasury.key(),
+        split_recipients,
+        payment_mint: None,
+        amount: sol_amount,
+        timestamp,
+    });
+
+    msg!("🏦 Withdrew {} lamports to {}",
+         sol_amount, if split_recipients > 0 { "revenue split" } else { "treasury" });
+    Ok(())
+}
+
+// Token counterpart of withdraw for one payment currency. With a split, remaining_accounts
+// starts with each recipient's writable associated token account for the mint, in split
+// order. Any transfer-hook accounts for the mint follow.
+pub fn withdraw_token<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawToken<'info>>, amount: u64) -> Result<()> {
+    let caps = ctx.accounts.config.caps;
+    require!(!caps.escrow || caps.finalized, ErrorCode::FundsLocked);
+    require!(amount > 0, ErrorCode::InvalidAmount);
+    require!(amount <= ctx.accounts.vault_payment_ata.amount, ErrorCode::InsufficientVaultBalance);
+
+    let timestamp = Clock::get()?.unix_timestamp;
+    let vault_bump = ctx.bumps.vault;
+    let payment_mint = &ctx.accounts.payment_mint;
+    let split = load_revenue_split(&ctx.accounts.revenue_split)?;
+    let split_recipients = split.as_ref().map_or(0, |split| split.recipients.len() as u8);
+
+    match split {
+        None => transfer_token_from_vault(
+            &ctx.accounts.vault_payment_ata,
+            payment_mint,
+            &ctx.accounts.treasury_payment_ata.to_account_info(),
+            &ctx.accounts.vault,
+            &ctx.accounts.token_program,
+            ctx.remaining_accounts,
+            vault_bump,
+            amount,
+        )?,
+        Some(split) => {
+            require!(
+                ctx.remaining_accounts.len() >= split.recipients.len(),
+                ErrorCode::InvalidRecipientAccount
+            );
+            let (accounts, hook_accounts) = ctx.remaining_accounts.split_at(split.recipients.len());
+
+            for ((recipient, token_account), share) in split.recipients.iter().zip(accounts).zip(split.shares(amount)) {
+                require_keys_eq!(
+                    token_account.key(),
+                    get_associated_token_address_with_program_id(
+                        &recipient.wallet,
+                        &payment_mint.key(),
+                        &ctx.accounts.token_program.key(),
+                    ),
+                    ErrorCode::InvalidRecipientAccount
+                );
+                if share > 0 {
+                    transfer_token_from_vault(
+                        &ctx.accounts.vault_payment_ata,
+                        payment_mint,
+                        token_account,
+                        &ctx.accounts.vault,
+                        &ctx.accounts.token_program,
+                        hook_accounts,
+                        vault_bump,
+                        share,
+                    )?;
+                }
+
+                emit!(RevenueSharePaid {
+                    recipient: recipient.wallet,
+                    bps: recipient.bps,
+                    payment_mint: Some(payment_mint.key()),
+                    amount: share,
+                    timestamp,
+                });
+            }
+        }
+    }
+
+    emit!(FundsWithdrawn {
+        withdrawn_by: ctx.accounts.payer.key(),
+        treasury: ctx.accounts.treasury.key(),
+        split_recipients,
+        payment_mint: Some(payment_mint.key()),
+        amount,
+        timestamp,
+    });
+
+    msg!("🏦 Withdrew {} of {} to {}",
+         amount, payment_mint.key(), if split_recipients > 0 { "revenue split" } else { "treasury" });
+    Ok(())
+}
+
+// The configured split, or None if set_revenue_split was never called
+fn load_revenue_split(account: &AccountInfo) -> Result<Option<RevenueSplit>> {
+    if account.data_is_empty() {
+        return Ok(None);
+    }
+    require_keys_eq!(*account.owner, crate::ID, ErrorCode::InvalidRevenueSplit);
+    let data = account.try_borrow_data()?;
+    Ok(Some(RevenueSplit::try_deserialize(&mut &data[..])?))
+}
+
+// Move lamports out of the vault PDA
+pub(crate) fn transfer_sol_from_vault<'info>(
+    vault: &SystemAccount<'info>,
+    to: &AccountInfo<'info>,
+    system_program: &Program<'info, System>,
+    vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"vault", &[vault_bump]]];
+    let cpi_context = CpiContext::new_with_signer(
+        system_program.to_account_info(),
+        anchor_lang::system_program::Transfer {
+            from: vault.to_account_info(),
+            to: to.clone(),
+        },
+        signer_seeds,
+    );
+    anchor_lang::system_program::transfer(cpi_context, amount)
+}
+
+// Move a payment currency out of a vault token account
+#[allow(clippy::too_many_arguments)]
+pub(crate) fn transfer_token_from_vault<'info>(
+    from: &InterfaceAccount<'info, TokenAccount>,
+    mint: &InterfaceAccount<'info, Mint>,
+    to: &AccountInfo<'info>,
+    vault: &SystemAccount<'info>,
+    token_program: &Interface<'info, TokenInterface>,
+    hook_accounts: &[AccountInfo<'info>],
+    vault_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    token_transfer::transfer_checked(
+        &token_program.to_account_info(),
+        from.to_account_info(),
+        mint.to_account_info(),
+        to.clone(),
+        vault.to_account_info(),
+        hook_accounts,
+        amount,
+        mint.decimals,
+        &[&[b"vault", &[vault_bump]]],
+    )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::assert_error;
+    use crate::state::{Round, RoundParams, MAX_PAYMENT_CURRENCIES};
+
+    const END: i64 = 2_000;
+
+    // Two rounds ending at END, the second one vesting
+    fn schedule(sold: [u64; 2]) -> RoundSchedule {
+        let round = |start_time, end_time, vesting_duration, tokens_sold| Round {
+            tokens_sold,
+            ..Round::from(RoundParams {
+                start_time,
+                end_time,
+                token_cap: 1_000_000,
+                discount_bps: 0,
+                vesting_cliff: 0,
+                vesting_duration,
+                min_purchase: 0,
+                max_per_wallet: 0,
+            })
+        };
+        RoundSchedule {
+            rounds: vec![round(1_000, 1_500, 0, sold[0]), round(1_500, END, 600, sold[1])],
+            bump: 255,
+        }
+    }
+
+    fn escrow_caps(soft_cap: u64) -> SaleCaps {
+        SaleCaps { escrow: true, soft_cap, hard_cap: 0, finalized: false }
+    }
+
+    fn buyer_record(tokens_per_round: [u64; 2]) -> BuyerRecord {
+        let mut record = BuyerRecord {
+            buyer: Pubkey::new_unique(),
+            tokens_per_round: [0; MAX_ROUNDS],
+            total_tokens: tokens_per_round.iter().sum(),
+            total_paid_sol: 0,
+            paid_per_currency: [0; MAX_PAYMENT_CURRENCIES],
+            first_purchase_time: 0,
+            last_purchase_time: 0,
+            purchase_count: 0,
+            referrer: Pubkey::default(),
+            bump: 255,
+        };
+        record.tokens_per_round[..2].copy_from_slice(&tokens_per_round);
+        record
+    }
+
+    #[test]
+    fn refund_opens_after_missed_soft_cap() {
+        let schedule = schedule([300, 400]);
+        let caps = escrow_caps(1_000);
+        check_refund_window(&caps, &schedule, END).unwrap();
+        check_refund_window(&caps, &schedule, END + 1_000).unwrap();
+
+        assert_error(check_refund_window(&caps, &schedule, END - 1), ErrorCode::SaleNotEnded);
+        assert_error(check_refund_window(&escrow_caps(700), &schedule, END), ErrorCode::SoftCapReached);
+        let no_escrow = SaleCaps { escrow: false, ..caps };
+        assert_error(check_refund_window(&no_escrow, &schedule, END), ErrorCode::EscrowDisabled);
+    }
+
+    #[test]
+    fn finalize_closes_refunds() {
+        let schedule = schedule([500, 500]);
+        let mut caps = escrow_caps(1_000);
+        assert_error(check_finalize_window(&caps, &schedule, END - 1), ErrorCode::SaleNotEnded);
+        check_finalize_window(&caps, &schedule, END).unwrap();
+
+        caps.finalized = true;
+        assert_error(check_refund_window(&caps, &schedule, END), ErrorCode::AlreadyFinalized);
+        assert_error(check_finalize_window(&caps, &schedule, END), ErrorCode::AlreadyFinalized);
+    }
+
+    #[test]
+    fn finalize_needs_soft_cap() {
+        let schedule = schedule([500, 499]);
+        assert_error(check_finalize_window(&escrow_caps(1_000), &schedule, END), ErrorCode::SoftCapNotReached);
+        let no_escrow = SaleCaps { escrow: false, ..escrow_caps(0) };
+        assert_error(check_finalize_window(&no_escrow, &schedule, END), ErrorCode::EscrowDisabled);
+    }
+
+    #[test]
+    fn caps_locked_once_sales_exist() {
+        let referral = ReferralConfig::default();
+        check_sale_caps(&schedule([0, 0]), &referral, true, 1_000, 2_000).unwrap();
+        assert_error(check_sale_caps(&schedule([1, 0]), &referral, true, 1_000, 2_000), ErrorCode::CapsLocked);
+        assert_error(check_sale_caps(&schedule([0, 1]), &referral, false, 0, 0), ErrorCode::CapsLocked);
+    }
+
+    #[test]
+    fn invalid_caps_are_rejected() {
+        let schedule = schedule([0, 0]);
+        let referral = ReferralConfig::default();
+        check_sale_caps(&schedule, &referral, false, 0, 0).unwrap();
+        check_sale_caps(&schedule, &referral, true, 1_000, 0).unwrap();
+        assert_error(check_sale_caps(&schedule, &referral, false, 1_000, 0), ErrorCode::InvalidSaleCaps);
+        assert_error(check_sale_caps(&schedule, &referral, true, 2_001, 2_000), ErrorCode::InvalidSaleCaps);
+
+        let referral = ReferralConfig { enabled: true, ..referral };
+        check_sale_caps(&schedule, &referral, false, 0, 0).unwrap();
+        assert_error(check_sale_caps(&schedule, &referral, true, 0, 0), ErrorCode::ReferralEscrowConflict);
+    }
+
+    #[test]
+    fn refunds_keep_the_soft_cap_missed() {
+        // 700 of a 1_000 soft cap sold; a refund of 300 can't be made up by new sales
+        let mut schedule = schedule([300, 400]);
+        let mut record = buyer_record([100, 200]);
+        release_refunded_tokens(&mut schedule, &record);
+        clear_refunded_purchases(&mut record);
+        assert_eq!(schedule.rounds[0].tokens_sold, 200);
+        assert_eq!(schedule.rounds[1].tokens_sold, 200);
+        assert_eq!(schedule.total_tokens_sold(), 400);
+        check_refund_window(&escrow_caps(1_000), &schedule, END).unwrap();
+
+        // A second refund call releases nothing more
+        release_refunded_tokens(&mut schedule, &record);
+        assert_eq!(schedule.total_tokens_sold(), 400);
+    }
+
+    #[test]
+    fn token_payments_refund_after_tokens_are_burned() {
+        let mut record = buyer_record([100, 0]);
+        record.total_paid_sol = 1_000;
+        record.paid_per_currency[1] = 50;
+        assert_error(token_refund_amount(&record, 1), ErrorCode::TokensNotRefunded);
+
+        clear_refunded_purchases(&mut record);
+        assert_eq!(record.total_paid_sol, 0);
+        assert_eq!(token_refund_amount(&record, 1).unwrap(), 50);
+        assert_error(token_refund_amount(&record, 0), ErrorCode::NothingToRefund);
+    }
+
+    #[test]
+    fn vesting_purchases_are_detected() {
+        let schedule = schedule([0, 0]);
+        assert!(!bought_in_vesting_round(&buyer_record([0, 0]), &schedule));
+        assert!(!bought_in_vesting_round(&buyer_record([100, 0]), &schedule));
+        assert!(bought_in_vesting_round(&buyer_record([100, 1]), &schedule));
+    }
+}
@@ -1,8 +1,22 @@
 pub mod admin;
 pub mod sale;
 pub mod airdrop;
+pub mod rounds;
+pub mod pricing;
+pub mod vesting;
+pub mod escrow;
+pub mod revenue;
+pub mod referral;
+pub mod payment;
 
 // Re-export for easy access
 pub use admin::*;
 pub use sale::*;
-pub use airdrop::*;
\ No newline at end of file
+pub use airdrop::*;
+pub use rounds::*;
+pub use pricing::*;
+pub use vesting::*;
+pub use escrow::*;
+pub use revenue::*;
+pub use referral::*;
+pub use payment::*;
\ No newline at end of file
@@ -0,0 +1,118 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
+use crate::state::{AcceptedCurrency, Config, PaymentRegistry, PriceSource, MAX_PAYMENT_CURRENCIES};
+use crate::error::ErrorCode;
+
+// Registers a payment currency and creates the vault's token account for it
+#[derive(Accounts)]
+pub struct AddPaymentCurrency<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PaymentRegistry::INIT_SPACE,
+        seeds = [b"payment_registry"],
+        bump
+    )]
+    pub payment_registry: Account<'info, PaymentRegistry>,
+
+    #[account(mint::token_program = token_program)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
+
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        associated_token::mint = payment_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = token_program,
+    )]
+    pub vault_payment_ata: InterfaceAccount<'info, TokenAccount>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub token_program: Interface<'info, TokenInterface>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct SetPaymentCurrency<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(mut, seeds = [b"payment_registry"], bump = payment_registry.bump)]
+    pub payment_registry: Account<'info, PaymentRegistry>,
+
+    pub payer: Signer<'info>,
+}
+
+fn validate_price_source(price_source: &PriceSource) -> Result<()> {
+    if let PriceSource::Oracle { feed, feed_id } = price_source {
+        require!(
+            *feed != Pubkey::default() || *feed_id != [0u8; 32],
+            ErrorCode::InvalidPythAccount
+        );
+    }
+    Ok(())
+}
+
+pub fn add_payment_currency(ctx: Context<AddPaymentCurrency>, price_source: PriceSource) -> Result<()> {
+    validate_price_source(&price_source)?;
+
+    let mint = &ctx.accounts.payment_mint;
+    let registry = &mut ctx.accounts.payment_registry;
+    require!(
+        registry.currency(&mint.key()).is_err(),
+        ErrorCode::CurrencyAlreadyRegistered
+    );
+    require!(registry.currencies.len() < MAX_PAYMENT_CURRENCIES, ErrorCode::TooManyCurrencies);
+
+    registry.currencies.push(AcceptedCurrency {
+        mint: mint.key(),
+        decimals: mint.decimals,
+        token_program: ctx.accounts.token_program.key(),
+        price_source,
+        enabled: true,
+    });
+    registry.bump = ctx.bumps.payment_registry;
+
+    msg!("💳 Payment currency #{} added: {} ({:?}), vault account {}",
+         registry.currencies.len() - 1, mint.key(), price_source, ctx.accounts.vault_payment_ata.key());
+    Ok(())
+}
+
+// Enable/disable a currency or change how it is priced. Disabled currencies stop
+// accepting purchases but can still be refunded and withdrawn.
+pub fn set_payment_currency(
+    ctx: Context<SetPaymentCurrency>,
+    mint: Pubkey,
+    enabled: bool,
+    price_source: PriceSource,
+) -> Result<()> {
+    validate_price_source(&price_source)?;
+
+    let registry = &mut ctx.accounts.payment_registry;
+    let (index, _) = registry.currency(&mint)?;
+    let currency = &mut registry.currencies[index];
+    currency.enabled = enabled;
+    currency.price_source = price_source;
+
+    msg!("💳 Payment currency #{} {}: {} ({:?})",
+         index, if enabled { "enabled" } else { "disabled" }, mint, price_source);
+    Ok(())
+}
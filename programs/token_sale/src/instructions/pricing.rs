@@ -0,0 +1,65 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, PricingParams, PricingCurveUpdated};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct UpdatePricingParams<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + PricingParams::INIT_SPACE,
+        seeds = [b"pricing_params"],
+        bump
+    )]
+    pub pricing_params: Account<'info, PricingParams>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn update_pricing_params(
+    ctx: Context<UpdatePricingParams>,
+    price_max: u64,
+    price_min: u64,
+    midpoint: u64,
+    steepness: u64,
+) -> Result<()> {
+    PricingParams::validate(price_max, price_min, steepness)?;
+
+    let params = &mut ctx.accounts.pricing_params;
+    let timestamp = Clock::get()?.unix_timestamp;
+
+    // Emit before overwriting so the event carries the previous curve
+    emit!(PricingCurveUpdated {
+        updated_by: ctx.accounts.payer.key(),
+        old_price_max: params.price_max,
+        old_price_min: params.price_min,
+        old_midpoint: params.midpoint,
+        old_steepness: params.steepness,
+        new_price_max: price_max,
+        new_price_min: price_min,
+        new_midpoint: midpoint,
+        new_steepness: steepness,
+        timestamp,
+    });
+
+    params.price_max = price_max;
+    params.price_min = price_min;
+    params.midpoint = midpoint;
+    params.steepness = steepness;
+    params.last_updated = timestamp;
+    params.bump = ctx.bumps.pricing_params;
+
+    msg!("📈 Pricing curve updated: max={}, min={}, midpoint={}, steepness={}",
+         price_max, price_min, midpoint, steepness);
+    Ok(())
+}
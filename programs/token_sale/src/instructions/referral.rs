@@ -0,0 +1,122 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::{self as token22, MintTo, Token2022};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{BuyerRecord, Config, ReferralConfig, ReferralRecord};
+use crate::error::ErrorCode;
+
+// Referrers must have bought before they can register. Since a buyer's referrer is fixed
+// at their first purchase, every referrer bought before the buyers they refer, so
+// referral chains can't loop back on themselves.
+#[derive(Accounts)]
+pub struct RegisterReferrer<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.initialized @ ErrorCode::NotInitialized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        seeds = [b"buyer_record", referrer.key().as_ref()],
+        bump = buyer_record.bump
+    )]
+    pub buyer_record: Account<'info, BuyerRecord>,
+
+    #[account(
+        init,
+        payer = referrer,
+        space = 8 + ReferralRecord::INIT_SPACE,
+        seeds = [b"referral", referrer.key().as_ref()],
+        bump
+    )]
+    pub referral_record: Account<'info, ReferralRecord>,
+
+    #[account(mut)]
+    pub referrer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+    require!(ctx.accounts.config.referral.enabled, ErrorCode::InvalidReferralConfig);
+    require!(ctx.accounts.buyer_record.purchase_count > 0, ErrorCode::ReferrerNotEligible);
+
+    let record = &mut ctx.accounts.referral_record;
+    record.referrer = ctx.accounts.referrer.key();
+    record.upline = ctx.accounts.buyer_record.referrer;
+    record.bump = ctx.bumps.referral_record;
+
+    msg!("🤝 Referrer registered: {}", record.referrer);
+    Ok(())
+}
+
+// Referrer for this purchase. The referral record passed on the buyer's first purchase
+// fixes their referrer; every later purchase must pass that same record.
+pub fn resolve_referrer(
+    referral: &ReferralConfig,
+    buyer_record: &BuyerRecord,
+    buyer: &Pubkey,
+    referral_record: Option<&ReferralRecord>,
+) -> Result<Option<Pubkey>> {
+    if !referral.enabled {
+        return Ok(None);
+    }
+
+    if buyer_record.referrer == Pubkey::default() {
+        let Some(record) = referral_record else {
+            return Ok(None);
+        };
+        require!(buyer_record.purchase_count == 0, ErrorCode::ReferrerLocked);
+        require_keys_neq!(record.referrer, *buyer, ErrorCode::SelfReferral);
+        require_keys_neq!(record.upline, *buyer, ErrorCode::ReferralLoop);
+        return Ok(Some(record.referrer));
+    }
+
+    let record = referral_record.ok_or(ErrorCode::ReferrerRequired)?;
+    require_keys_eq!(record.referrer, buyer_record.referrer, ErrorCode::ReferrerMismatch);
+    Ok(Some(record.referrer))
+}
+
+// The referrer's wallet, for SOL payment shares
+pub(crate) fn referrer_wallet<'info>(
+    account: Option<&UncheckedAccount<'info>>,
+    referrer: &Pubkey,
+) -> Result<AccountInfo<'info>> {
+    let account = account.ok_or(ErrorCode::ReferrerRequired)?;
+    require_keys_eq!(account.key(), *referrer, ErrorCode::ReferrerMismatch);
+    Ok(account.to_account_info())
+}
+
+// The referrer's token account for `mint` - sale tokens for bonuses, the payment currency for payment shares
+pub(crate) fn referrer_token_account<'info>(
+    account: Option<&InterfaceAccount<'info, TokenAccount>>,
+    referrer: &Pubkey,
+    mint: &Pubkey,
+) -> Result<AccountInfo<'info>> {
+    let account = account.ok_or(ErrorCode::ReferrerRequired)?;
+    require_keys_eq!(account.owner, *referrer, ErrorCode::ReferrerMismatch);
+    require_keys_eq!(account.mint, *mint, ErrorCode::ReferrerMismatch);
+    Ok(account.to_account_info())
+}
+
+// Mint bonus tokens to the referrer with the mint authority PDA
+pub(crate) fn mint_referral_bonus<'info>(
+    mint: &InterfaceAccount<'info, Mint>,
+    to: AccountInfo<'info>,
+    mint_authority: &UncheckedAccount<'info>,
+    token_program: &Program<'info, Token2022>,
+    mint_authority_bump: u8,
+    amount: u64,
+) -> Result<()> {
+    let signer_seeds: &[&[&[u8]]] = &[&[b"mint", &[mint_authority_bump]]];
+    let cpi_ctx = CpiContext::new_with_signer(
+        token_program.to_account_info(),
+        MintTo {
+            mint: mint.to_account_info(),
+            to,
+            authority: mint_authority.to_account_info(),
+        },
+        signer_seeds,
+    );
+    token22::mint_to(cpi_ctx, amount)
+}
@@ -0,0 +1,41 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, Recipient, RevenueSplit};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct SetRevenueSplit<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RevenueSplit::INIT_SPACE,
+        seeds = [b"revenue_split"],
+        bump
+    )]
+    pub revenue_split: Account<'info, RevenueSplit>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+// Replace the revenue split table applied by withdraw
+pub fn set_revenue_split(ctx: Context<SetRevenueSplit>, recipients: Vec<Recipient>) -> Result<()> {
+    RevenueSplit::validate(&recipients)?;
+
+    let split = &mut ctx.accounts.revenue_split;
+    split.recipients = recipients;
+    split.bump = ctx.bumps.revenue_split;
+
+    for recipient in split.recipients.iter() {
+        msg!("💸 {} -> {}bps", recipient.wallet, recipient.bps);
+    }
+    Ok(())
+}
@@ -0,0 +1,199 @@
+use anchor_lang::prelude::*;
+use crate::state::{Config, ReferralRewardKind, Round, RoundParams, RoundSchedule, SaleCaps, MAX_ROUNDS};
+use crate::error::ErrorCode;
+use crate::instructions::airdrop::LEAF_VERSION_V1;
+
+#[derive(Accounts)]
+pub struct AddRound<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    // Created with the first round
+    #[account(
+        init_if_needed,
+        payer = payer,
+        space = 8 + RoundSchedule::INIT_SPACE,
+        seeds = [b"round_schedule"],
+        bump
+    )]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    #[account(mut)]
+    pub payer: Signer<'info>,
+
+    pub system_program: Program<'info, System>,
+}
+
+#[derive(Accounts)]
+pub struct ManageRounds<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.owner == payer.key() @ ErrorCode::Unauthorized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"round_schedule"],
+        bump = round_schedule.bump
+    )]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    pub payer: Signer<'info>,
+}
+
+// Bonus-token referral rewards are minted at purchase, so they can't be paid in vesting rounds
+fn check_referral_vesting(config: &Config, round: &Round) -> Result<()> {
+    let referral = config.referral;
+    require!(
+        !(round.has_vesting() && referral.enabled && referral.reward_kind == ReferralRewardKind::BonusTokens),
+        ErrorCode::ReferralVestingConflict
+    );
+    Ok(())
+}
+
+// Refunds and finalization of an escrowed sale hinge on its end time, so the schedule
+// is frozen once that has passed
+fn check_escrow_sale_open(caps: &SaleCaps, schedule: &RoundSchedule, now: i64) -> Result<()> {
+    let ended = !schedule.rounds.is_empty() && now >= schedule.sale_end_time();
+    require!(!(caps.escrow && ended), ErrorCode::EscrowSaleEnded);
+    Ok(())
+}
+
+// Append a round after the last configured one
+pub fn add_round(ctx: Context<AddRound>, params: RoundParams) -> Result<()> {
+    let schedule = &mut ctx.accounts.round_schedule;
+    schedule.bump = ctx.bumps.round_schedule;
+
+    let now = Clock::get()?.unix_timestamp;
+    check_escrow_sale_open(&ctx.accounts.config.caps, schedule, now)?;
+    require!(schedule.rounds.len() < MAX_ROUNDS, ErrorCode::TooManyRounds);
+    require!(params.start_time > now, ErrorCode::RoundAlreadyStarted);
+
+    let round = Round::from(params);
+    let index = schedule.rounds.len();
+    schedule.validate_round_at(index, &round)?;
+    check_referral_vesting(&ctx.accounts.config, &round)?;
+    schedule.rounds.push(round);
+
+    msg!("🎯 Round {} added: {} -> {}, cap {} base units, {}bps discount, vesting {}s cliff + {}s",
+         index + 1, round.start_time, round.end_time, round.token_cap, round.discount_bps,
+         round.vesting_cliff, round.vesting_duration);
+    Ok(())
+}
+
+// Change a round that hasn't started yet
+pub fn edit_round(ctx: Context<ManageRounds>, round_index: u8, params: RoundParams) -> Result<()> {
+    let schedule = &mut ctx.accounts.round_schedule;
+    let index = round_index as usize;
+    let now = Clock::get()?.unix_timestamp;
+    check_escrow_sale_open(&ctx.accounts.config.caps, schedule, now)?;
+
+    let existing = schedule.rounds.get(index).ok_or(ErrorCode::RoundNotFound)?;
+    require!(!existing.has_started(now), ErrorCode::RoundAlreadyStarted);
+    require!(params.start_time > now, ErrorCode::RoundAlreadyStarted);
+
+    // The allowlist is managed separately with set_round_allowlist
+    let round = Round {
+        allowlist_root: existing.allowlist_root,
+        allowlist_leaf_version: existing.allowlist_leaf_version,
+        ..Round::from(params)
+    };
+    schedule.validate_round_at(index, &round)?;
+    check_referral_vesting(&ctx.accounts.config, &round)?;
+    schedule.rounds[index] = round;
+
+    msg!("🎯 Round {} updated: {} -> {}, cap {} base units, {}bps discount, vesting {}s cliff + {}s",
+         index + 1, round.start_time, round.end_time, round.token_cap, round.discount_bps,
+         round.vesting_cliff, round.vesting_duration);
+    Ok(())
+}
+
+// Remove a round that hasn't started yet - later rounds move up one slot
+pub fn close_round(ctx: Context<ManageRounds>, round_index: u8) -> Result<()> {
+    let schedule = &mut ctx.accounts.round_schedule;
+    let index = round_index as usize;
+    let now = Clock::get()?.unix_timestamp;
+    check_escrow_sale_open(&ctx.accounts.config.caps, schedule, now)?;
+
+    let existing = schedule.rounds.get(index).ok_or(ErrorCode::RoundNotFound)?;
+    require!(!existing.has_started(now), ErrorCode::RoundAlreadyStarted);
+
+    schedule.rounds.remove(index);
+
+    msg!("🎯 Round {} closed, {} rounds remaining", index + 1, schedule.rounds.len());
+    Ok(())
+}
+
+// Restrict a round to allowlisted wallets, or open it again with an all-zero root.
+// Can be changed while the round is running, e.g. to add late entries.
+pub fn set_round_allowlist(
+    ctx: Context<ManageRounds>,
+    round_index: u8,
+    allowlist_root: [u8; 32],
+    leaf_version: u8,
+) -> Result<()> {
+    require!(leaf_version <= LEAF_VERSION_V1, ErrorCode::InvalidLeafVersion);
+
+    let schedule = &mut ctx.accounts.round_schedule;
+    let index = round_index as usize;
+
+    let round = schedule.rounds.get_mut(index).ok_or(ErrorCode::RoundNotFound)?;
+    round.allowlist_root = allowlist_root;
+    round.allowlist_leaf_version = leaf_version;
+
+    if round.has_allowlist() {
+        msg!("📋 Round {} restricted to allowlist", index + 1);
+    } else {
+        msg!("📋 Round {} open to all wallets", index + 1);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::assert_error;
+
+    fn schedule(end_time: Option<i64>) -> RoundSchedule {
+        let rounds = end_time
+            .map(|end_time| {
+                Round::from(RoundParams {
+                    start_time: end_time - 100,
+                    end_time,
+                    token_cap: 1_000,
+                    discount_bps: 0,
+                    vesting_cliff: 0,
+                    vesting_duration: 0,
+                    min_purchase: 0,
+                    max_per_wallet: 0,
+                })
+            })
+            .into_iter()
+            .collect();
+        RoundSchedule { rounds, bump: 255 }
+    }
+
+    fn caps(escrow: bool) -> SaleCaps {
+        SaleCaps { escrow, ..SaleCaps::default() }
+    }
+
+    #[test]
+    fn ended_escrow_sale_freezes_rounds() {
+        let schedule = schedule(Some(1_000));
+        check_escrow_sale_open(&caps(true), &schedule, 999).unwrap();
+        assert_error(check_escrow_sale_open(&caps(true), &schedule, 1_000), ErrorCode::EscrowSaleEnded);
+        assert_error(check_escrow_sale_open(&caps(true), &schedule, 5_000), ErrorCode::EscrowSaleEnded);
+    }
+
+    #[test]
+    fn other_schedules_stay_open() {
+        check_escrow_sale_open(&caps(false), &schedule(Some(1_000)), 5_000).unwrap();
+        check_escrow_sale_open(&caps(true), &schedule(None), 5_000).unwrap();
+    }
+}
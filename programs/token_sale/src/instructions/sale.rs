@@ -1,48 +1,44 @@
 use anchor_lang::prelude::*;
 use anchor_spl::associated_token::AssociatedToken;
 use anchor_spl::token_2022::{self as token22, MintTo, Token2022};
-use anchor_spl::token_interface::{Mint, TokenAccount};
-use anchor_spl::token::{self, Transfer, Token};
-use pyth_sdk_solana::state::SolanaPriceAccount;
+use anchor_spl::token_interface::{Mint, TokenAccount, TokenInterface};
 use crate::state::*;
 use crate::error::ErrorCode;
+use crate::oracle::{self, Feed};
+use crate::curve;
+use crate::kyc;
+use crate::token_transfer;
+use crate::instructions::airdrop::verify_merkle_proof;
+use crate::instructions::referral::{
+    mint_referral_bonus, referrer_token_account, referrer_wallet, resolve_referrer,
+};
 
-// Hardcoded Pyth mainnet addresses for security (we'll use these as keys for mock data)
-pub const PYTH_EUR_USD_MAINNET: Pubkey = Pubkey::new_from_array([
-    0x45, 0x5f, 0x8d, 0x18, 0x19, 0x3a, 0x9c, 0x1a,
-    0x6b, 0x0e, 0x3c, 0x7a, 0x7f, 0x8d, 0x1f, 0x8a,
-    0x2c, 0x9c, 0x8e, 0x8f, 0x1a, 0x3c, 0x7a, 0x7f,
-    0x8d, 0x1f, 0x8a, 0x2c, 0x9c, 0x8e, 0x8f, 0x1a,
-]);
-
-pub const PYTH_SOL_USD_MAINNET: Pubkey = Pubkey::new_from_array([
-    0xef, 0x0d, 0x8b, 0x6f, 0x87, 0xda, 0x2b, 0x0e,
-    0x6f, 0x9a, 0x1c, 0x8d, 0x8f, 0x8e, 0x8a, 0x2c,
-    0x9c, 0x8e, 0x8f, 0x1a, 0x3c, 0x7a, 0x7f, 0x8d,
-    0x1f, 0x8a, 0x2c, 0x9c, 0x8e, 0x8f, 0x1a, 0x3c,
-]);
-pub const USDC_MINT: Pubkey = pubkey!("4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU"); // Mainnet USDC
-pub const EURC_MINT: Pubkey = pubkey!("HzwqbKZw8HxMN6bF2yFZNrht3c2iXXzpKcFu7uBEDKtr"); // Mainnet EURC
 // Account structs
 #[derive(Accounts)]
 pub struct Buy<'info> {
     #[account(
-        mut,
         seeds = [b"token_sale_config"],
         bump = config.bump,
         constraint = config.initialized @ ErrorCode::NotInitialized
     )]
     pub config: Account<'info, Config>,
 
-    #[account(mut)]
-    pub buyer: Signer<'info>,
-
-    /// CHECK: Treasury account
     #[account(
         mut,
-        address = config.treasury @ ErrorCode::InvalidTreasury
+        seeds = [b"round_schedule"],
+        bump = round_schedule.bump
     )]
-    pub treasury: UncheckedAccount<'info>,
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    #[account(seeds = [b"pricing_params"], bump = pricing_params.bump)]
+    pub pricing_params: Account<'info, PricingParams>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    // Program vault - all SOL payments land here until withdrawn
+    #[account(mut, seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
 
     #[account(
         mut,
@@ -66,13 +62,47 @@ pub struct Buy<'info> {
     )]
     pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
 
-    // Hardcoded Pyth accounts - we'll use these addresses to determine which mock price to use
-    /// CHECK: Pyth EUR/USD price account (hardcoded address)
-    #[account(address = PYTH_EUR_USD_MAINNET @ ErrorCode::InvalidPythAccount)]
+    // Only needed when the round vests
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VestingAccount::INIT_SPACE,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump
+    )]
+    pub vesting: Option<Account<'info, VestingAccount>>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerRecord::INIT_SPACE,
+        seeds = [b"buyer_record", buyer.key().as_ref()],
+        bump
+    )]
+    pub buyer_record: Account<'info, BuyerRecord>,
+
+    /// CHECK: Instructions sysvar, read for the KYC attestation
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
+
+    // Referral accounts - optional. Passing a referral record on the buyer's first purchase
+    // sets their referrer; later purchases must pass the same record.
+    #[account(mut)]
+    pub referral_record: Option<Account<'info, ReferralRecord>>,
+
+    /// CHECK: Referrer's wallet, checked against referral_record - receives SOL payment shares
+    #[account(mut)]
+    pub referrer: Option<UncheckedAccount<'info>>,
+
+    // Referrer's token account for the reward - sale tokens for bonuses, unused for SOL payment shares
+    #[account(mut)]
+    pub referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
+
+    // Pyth accounts - legacy price accounts or PriceUpdateV2, depending on config.oracle.kind
+    /// CHECK: Pyth EUR/USD price account (validated in oracle::get_price)
     pub pyth_eur_usd_account: AccountInfo<'info>,
 
-    /// CHECK: Pyth SOL/USD price account (hardcoded address)
-    #[account(address = PYTH_SOL_USD_MAINNET @ ErrorCode::InvalidPythAccount)]
+    /// CHECK: Pyth SOL/USD price account (validated in oracle::get_price)
     pub pyth_sol_usd_account: AccountInfo<'info>,
 
     pub token_2022_program: Program<'info, Token2022>,
@@ -81,33 +111,41 @@ pub struct Buy<'info> {
 }
 
 #[derive(Accounts)]
-pub struct BuyWithEurc<'info> {
+pub struct BuyWithToken<'info> {
     #[account(
-        mut,
         seeds = [b"token_sale_config"],
         bump = config.bump,
         constraint = config.initialized @ ErrorCode::NotInitialized
     )]
     pub config: Account<'info, Config>,
 
+    #[account(
+        mut,
+        seeds = [b"round_schedule"],
+        bump = round_schedule.bump
+    )]
+    pub round_schedule: Account<'info, RoundSchedule>,
+
+    #[account(seeds = [b"pricing_params"], bump = pricing_params.bump)]
+    pub pricing_params: Account<'info, PricingParams>,
+
+    #[account(seeds = [b"payment_registry"], bump = payment_registry.bump)]
+    pub payment_registry: Account<'info, PaymentRegistry>,
+
     #[account(mut)]
     pub buyer: Signer<'info>,
 
-    /// CHECK: Your token mint
     #[account(mut, address = config.mint @ ErrorCode::InvalidMint)]
     pub mint: InterfaceAccount<'info, Mint>,
 
-    /// CHECK: EURC mint address (fixed for EURC)
-    #[account(
-        address = EURC_MINT @ ErrorCode::InvalidEurcMint
-    )]
-    pub eurc_mint: InterfaceAccount<'info, Mint>,
+    // Registered payment currency, checked against payment_registry
+    #[account(mint::token_program = payment_token_program)]
+    pub payment_mint: InterfaceAccount<'info, Mint>,
 
     /// CHECK: Mint authority PDA
     #[account(seeds = [b"mint"], bump)]
     pub mint_authority_pda: UncheckedAccount<'info>,
 
-    /// CHECK: Buyer's YOUR token account
     #[account(
         init_if_needed,
         payer = buyer,
@@ -117,92 +155,71 @@ pub struct BuyWithEurc<'info> {
     )]
     pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: Buyer's EURC token account
+    // Only needed when the round vests
     #[account(
-        mut,
-        associated_token::mint = eurc_mint,
-        associated_token::authority = buyer,
-        associated_token::token_program = token_program,
+        init_if_needed,
+        payer = buyer,
+        space = 8 + VestingAccount::INIT_SPACE,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump
     )]
-    pub buyer_eurc_ata: InterfaceAccount<'info, TokenAccount>,
+    pub vesting: Option<Account<'info, VestingAccount>>,
 
-    /// CHECK: Treasury's EURC token account
     #[account(
-        mut,
-        associated_token::mint = eurc_mint,
-        associated_token::authority = config.treasury,
-        associated_token::token_program = token_program,
+        init_if_needed,
+        payer = buyer,
+        space = 8 + BuyerRecord::INIT_SPACE,
+        seeds = [b"buyer_record", buyer.key().as_ref()],
+        bump
     )]
-    pub treasury_eurc_ata: InterfaceAccount<'info, TokenAccount>,
-
-    pub token_2022_program: Program<'info, Token2022>,
-    pub token_program: Program<'info, Token>,  // For EURC transfers
-    pub associated_token_program: Program<'info, AssociatedToken>,
-    pub system_program: Program<'info, System>,
-}
+    pub buyer_record: Account<'info, BuyerRecord>,
 
-#[derive(Accounts)]
-pub struct BuyWithUsdc<'info> {
-    #[account(
-        mut,
-        seeds = [b"token_sale_config"],
-        bump = config.bump,
-        constraint = config.initialized @ ErrorCode::NotInitialized
-    )]
-    pub config: Account<'info, Config>,
+    /// CHECK: Instructions sysvar, read for the KYC attestation
+    #[account(address = anchor_lang::solana_program::sysvar::instructions::ID)]
+    pub instructions_sysvar: UncheckedAccount<'info>,
 
+    // Referral accounts - optional. Passing a referral record on the buyer's first purchase
+    // sets their referrer; later purchases must pass the same record.
     #[account(mut)]
-    pub buyer: Signer<'info>,
-
-    /// CHECK: Your token mint
-    #[account(mut, address = config.mint @ ErrorCode::InvalidMint)]
-    pub mint: InterfaceAccount<'info, Mint>,
+    pub referral_record: Option<Account<'info, ReferralRecord>>,
 
-    /// CHECK: USDC mint address (fixed for USDC)
-    #[account(
-        address = USDC_MINT @ ErrorCode::InvalidUsdcMint
-    )]
-    pub usdc_mint: InterfaceAccount<'info, Mint>,
-
-    /// CHECK: Mint authority PDA
-    #[account(seeds = [b"mint"], bump)]
-    pub mint_authority_pda: UncheckedAccount<'info>,
+    /// CHECK: Referrer's wallet, checked against referral_record - receives nothing directly (rewards go to its token account)
+    #[account(mut)]
+    pub referrer: Option<UncheckedAccount<'info>>,
 
-    /// CHECK: Buyer's YOUR token account
-    #[account(
-        init_if_needed,
-        payer = buyer,
-        associated_token::mint = mint,
-        associated_token::authority = buyer,
-        associated_token::token_program = token_2022_program,
-    )]
-    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+    // Referrer's token account for the reward - sale tokens for bonuses, the payment currency for payment shares
+    #[account(mut)]
+    pub referrer_token_account: Option<InterfaceAccount<'info, TokenAccount>>,
 
-    /// CHECK: Buyer's USDC token account
     #[account(
         mut,
-        associated_token::mint = usdc_mint,
+        associated_token::mint = payment_mint,
         associated_token::authority = buyer,
-        associated_token::token_program = token_program,
+        associated_token::token_program = payment_token_program,
     )]
-    pub buyer_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    pub buyer_payment_ata: InterfaceAccount<'info, TokenAccount>,
 
-    /// CHECK: Treasury's USDC token account
+    #[account(seeds = [b"vault"], bump)]
+    pub vault: SystemAccount<'info>,
+
+    // Program vault's account for the payment currency, created by add_payment_currency
     #[account(
         mut,
-        associated_token::mint = usdc_mint,
-        associated_token::authority = config.treasury,
-        associated_token::token_program = token_program,
+        associated_token::mint = payment_mint,
+        associated_token::authority = vault,
+        associated_token::token_program = payment_token_program,
     )]
-    pub treasury_usdc_ata: InterfaceAccount<'info, TokenAccount>,
+    pub vault_payment_ata: InterfaceAccount<'info, TokenAccount>,
 
-    // Hardcoded Pyth account for EUR/USD conversion
-    /// CHECK: Pyth EUR/USD price account (hardcoded address)
-    #[account(address = PYTH_EUR_USD_MAINNET @ ErrorCode::InvalidPythAccount)]
+    // Pyth accounts - only read when the currency's price source needs them
+    /// CHECK: Pyth EUR/USD price account (validated in oracle::get_price)
     pub pyth_eur_usd_account: AccountInfo<'info>,
 
+    /// CHECK: Pyth CUR/USD price account for oracle-priced currencies (validated in oracle::get_price)
+    pub pyth_currency_usd_account: Option<UncheckedAccount<'info>>,
+
     pub token_2022_program: Program<'info, Token2022>,
-    pub token_program: Program<'info, Token>,  // For USDC transfers
+    pub payment_token_program: Interface<'info, TokenInterface>,
     pub associated_token_program: Program<'info, AssociatedToken>,
     pub system_program: Program<'info, System>,
 }
@@ -211,225 +228,457 @@ pub struct BuyWithUsdc<'info> {
 pub struct GetRoundInfo<'info> {
     #[account(seeds = [b"token_sale_config"], bump = config.bump)]
     pub config: Account<'info, Config>,
-}
-
-// Mock price data - using realistic current prices
-pub const MOCK_EUR_USD_RATE: u64 = 1_080_000; // 1.08 EUR/USD (6 decimals)
-pub const MOCK_SOL_USD_RATE: u64 = 140_000_000; // 140.00 SOL/USD (6 decimals)
-pub const MOCK_USDC_USD_RATE: u64 = 1_000_000; // 1.00 USDC/USD (6 decimals)
 
-// Mock price function - uses account addresses to determine which price to return
-pub fn get_mock_price(price_account: &AccountInfo) -> Result<u64> {
-    msg!("🧪 Using mock price data");
-
-    // Determine which price to return based on the account address
-    if price_account.key == &PYTH_EUR_USD_MAINNET {
-        msg!("   EUR/USD rate: {}", MOCK_EUR_USD_RATE);
-        Ok(MOCK_EUR_USD_RATE)
-    } else if price_account.key == &PYTH_SOL_USD_MAINNET {
-        msg!("   SOL/USD rate: {}", MOCK_SOL_USD_RATE);
-        Ok(MOCK_SOL_USD_RATE)
-    } else {
-        // Default to USDC rate for any other account
-        msg!("   USDC/USD rate: {}", MOCK_USDC_USD_RATE);
-        Ok(MOCK_USDC_USD_RATE)
-    }
+    #[account(seeds = [b"round_schedule"], bump = round_schedule.bump)]
+    pub round_schedule: Account<'info, RoundSchedule>,
 }
 
-// Original Pyth function kept for reference but not used
-pub fn _get_validated_price(_price_account: &AccountInfo) -> Result<u64> {
-    // This is the original function - we're not using it anymore
-    Err(error!(ErrorCode::PriceNotAvailable))
+// Sigmoid pricing function - deterministic fixed-point curve shared with off-chain quoting.
+// `amount` is in base units of a sale token with `decimals` decimals.
+pub fn get_sigmoid_price_per_token(amount: u64, decimals: u8, params: &PricingParams, round: &Round) -> Result<u64> {
+    curve::price_per_token(amount, decimals, &params.curve(), round.discount_bps).ok_or(error!(ErrorCode::Overflow))
 }
-// Sigmoid pricing function
-pub fn get_sigmoid_price_per_token(amount: u64, round: Round) -> Result<u64> {
-    let amount_f64 = amount as f64;
-
-    // Sigmoid function: price = min + (max - min) / (1 + e^(-k*(x - midpoint)))
-    let exponent = SIGMOID_STEEPNESS * (amount_f64 - SIGMOID_MIDPOINT);
-    let denominator = 1.0 + exponent.exp();
-
-    let price_range = (BASE_PRICE_MAX - BASE_PRICE_MIN) as f64;
-    let base_price = BASE_PRICE_MIN as f64 + (price_range / denominator);
-
-    // Apply round discount
-    let discount_multiplier = round.get_discount_multiplier();
-    let discounted_price = base_price * discount_multiplier;
 
-    // Convert back to u64 (6 decimals) with bounds checking
-    let final_price = (discounted_price.round() as u64)
-        .max(BASE_PRICE_MIN)
-        .min(BASE_PRICE_MAX);
-    Ok(final_price)
-}
 // Helper functions
-pub fn get_stablecoin_price_per_token(amount: u64, round: Round) -> u64 {
-    get_sigmoid_price_per_token(amount, round).unwrap_or(BASE_PRICE_MAX)
+pub fn get_stablecoin_price_per_token(amount: u64, decimals: u8, params: &PricingParams, round: &Round) -> Result<u64> {
+    get_sigmoid_price_per_token(amount, decimals, params, round)
 }
 
-// Convert your EURC price to USDC (USDC = $1.00)
+// Convert your EURC price to USDC (USDC = $1.00), rounded up
 pub fn convert_eurc_price_to_usdc(eurc_price: u64, eur_usd_rate: u64) -> Result<u64> {
     // Convert EURC price to USDC: eurc_price * eur_usd_rate / 1_000_000
-    eurc_price
+    let scaled = eurc_price
         .checked_mul(eur_usd_rate)
-        .ok_or(error!(ErrorCode::Overflow))?
-        .checked_div(1_000_000)
-        .ok_or(error!(ErrorCode::Overflow))
+        .ok_or(error!(ErrorCode::Overflow))?;
+    checked_div_ceil(scaled, 1_000_000)
 }
 
-// Convert your EURC price to SOL
+// Convert your EURC price to SOL, rounded up
 pub fn convert_eurc_price_to_sol(eurc_price: u64, eur_usd_rate: u64, sol_usd_rate: u64) -> Result<u64> {
     // First convert EURC to USD
-    let usd_price = eurc_price
-        .checked_mul(eur_usd_rate)
-        .ok_or(error!(ErrorCode::Overflow))?
-        .checked_div(1_000_000)
-        .ok_or(error!(ErrorCode::Overflow))?;
+    let usd_price = convert_eurc_price_to_usdc(eurc_price, eur_usd_rate)?;
 
-    // Then convert USD to SOL (SOL has 9 decimals, but we want 6 decimal precision)
-    usd_price
+    // Then convert USD to lamports (SOL has 9 decimals)
+    let scaled = usd_price
         .checked_mul(1_000_000_000)
-        .ok_or(error!(ErrorCode::Overflow))?
-        .checked_div(sol_usd_rate)
-        .ok_or(error!(ErrorCode::Overflow))
-}
-
-pub fn get_tier_name(amount: u64) -> String {
-    match amount {
-        a if a < 1000 => "Tier 1 (<1K)".to_string(),
-        a if a < 5000 => "Tier 2 (1K-5K)".to_string(),
-        a if a < 10000 => "Tier 3 (5K-10K)".to_string(),
-        a if a < 25000 => "Tier 4 (10K-25K)".to_string(),
-        _ => "Tier 5 (25K+)".to_string(),
+        .ok_or(error!(ErrorCode::Overflow))?;
+    checked_div_ceil(scaled, sol_usd_rate)
+}
+
+// EUR/USD and CUR/USD rates (6 decimals) used to price a payment currency.
+// EUR-priced currencies report 1.00 for both.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct CurrencyRates {
+    pub eur_usd: u64,
+    pub currency_usd: u64,
+}
+
+const ONE_USD: u64 = 10u64.pow(PRICE_DECIMALS as u32);
+
+// Oracle rates needed to price `currency`
+pub fn get_currency_rates(
+    currency: &AcceptedCurrency,
+    oracle_config: &OracleConfig,
+    eur_usd_account: &AccountInfo,
+    currency_usd_account: Option<&AccountInfo>,
+) -> Result<CurrencyRates> {
+    match currency.price_source {
+        PriceSource::Eur => Ok(CurrencyRates { eur_usd: ONE_USD, currency_usd: ONE_USD }),
+        PriceSource::UsdPegged => Ok(CurrencyRates {
+            eur_usd: oracle::get_price(eur_usd_account, oracle_config, Feed::EurUsd)?,
+            currency_usd: ONE_USD,
+        }),
+        PriceSource::Oracle { feed, feed_id } => {
+            let currency_usd_account = currency_usd_account.ok_or(ErrorCode::PythAccountNotFound)?;
+            Ok(CurrencyRates {
+                eur_usd: oracle::get_price(eur_usd_account, oracle_config, Feed::EurUsd)?,
+                currency_usd: oracle::get_price(
+                    currency_usd_account,
+                    oracle_config,
+                    Feed::Currency { account: feed, feed_id },
+                )?,
+            })
+        }
     }
 }
 
-pub fn get_current_round(config: &Config) -> Result<Round> {
-    let current_time = Clock::get()?.unix_timestamp;
-    let round_duration: i64 = 30 * 24 * 60 * 60; // 30 days in seconds
+// Convert your EURC price to a payment currency with `decimals` decimals, rounded up
+pub fn convert_eurc_price_to_currency(eurc_price: u64, decimals: u8, rates: CurrencyRates) -> Result<u64> {
+    require!(rates.currency_usd > 0, ErrorCode::InvalidPrice);
 
-    let elapsed_time = current_time - config.sale_start_time;
+    // eurc_price * eur_usd / currency_usd, rescaled from 6 to `decimals` decimals
+    let scaled = (eurc_price as u128)
+        .checked_mul(rates.eur_usd as u128)
+        .and_then(|value| value.checked_mul(10u128.checked_pow(decimals as u32)?))
+        .ok_or(error!(ErrorCode::Overflow))?;
+    let divisor = rates.currency_usd as u128 * ONE_USD as u128;
+    u64::try_from(scaled.div_ceil(divisor)).map_err(|_| error!(ErrorCode::Overflow))
+}
 
-    if elapsed_time < round_duration {
-        Ok(Round::First)
-    } else if elapsed_time < round_duration * 2 {
-        Ok(Round::Second)
-    } else if elapsed_time < round_duration * 3 {
-        Ok(Round::Third)
-    } else {
-        // Sale ended after 90 days
-        err!(ErrorCode::SaleEnded)
-    }
+// Ceiling division - conversions round in the treasury's favor
+fn checked_div_ceil(value: u64, divisor: u64) -> Result<u64> {
+    require!(divisor > 0, ErrorCode::Overflow);
+    Ok(value.div_ceil(divisor))
+}
+
+// Tier by whole tokens purchased (`amount` in base units)
+pub fn get_tier_name(amount: u64, decimals: u8) -> Result<String> {
+    let unit = 10u64.checked_pow(decimals as u32).ok_or(ErrorCode::Overflow)?;
+    let tier = match amount / unit {
+        a if a < 1000 => "Tier 1 (<1K)",
+        a if a < 5000 => "Tier 2 (1K-5K)",
+        a if a < 10000 => "Tier 3 (5K-10K)",
+        a if a < 25000 => "Tier 4 (10K-25K)",
+        _ => "Tier 5 (25K+)",
+    };
+    Ok(tier.to_string())
 }
 
-pub fn check_round_limit(config: &Config, amount: u64) -> Result<()> {
-    let current_round = get_current_round(config)?;
-    let round_tokens_sold = get_round_tokens_sold(config, current_round);
+pub fn get_current_round(schedule: &RoundSchedule) -> Result<usize> {
+    let current_time = Clock::get()?.unix_timestamp;
+    schedule.current_round_index(current_time)
+}
 
-    let remaining_tokens = TOKENS_PER_ROUND.checked_sub(round_tokens_sold)
-        .ok_or(ErrorCode::Overflow)?;
+pub fn check_round_limit(schedule: &RoundSchedule, round_index: usize, amount: u64) -> Result<()> {
+    let round = schedule.rounds.get(round_index).ok_or(ErrorCode::RoundNotFound)?;
 
     require!(
-        amount <= remaining_tokens,
+        amount <= round.remaining_tokens(),
         ErrorCode::RoundLimitExceeded
     );
 
     Ok(())
 }
 
-pub fn get_round_tokens_sold(config: &Config, round: Round) -> u64 {
-    match round {
-        Round::First => config.round1_tokens_sold,
-        Round::Second => config.round2_tokens_sold,
-        Round::Third => config.round3_tokens_sold,
+pub fn check_hard_cap(caps: &SaleCaps, schedule: &RoundSchedule, amount: u64) -> Result<()> {
+    require!(
+        amount <= caps.remaining(schedule.total_tokens_sold()),
+        ErrorCode::HardCapReached
+    );
+    Ok(())
+}
+
+// Supply left for the purchase itself once the referral bonus, if any, is set aside
+pub fn purchasable_supply(
+    referral: &ReferralConfig,
+    referred: bool,
+    caps: &SaleCaps,
+    schedule: &RoundSchedule,
+    round: &Round,
+) -> u64 {
+    let supply = round.remaining_tokens().min(caps.remaining(schedule.total_tokens_sold()));
+    if referred {
+        referral.max_purchase_within(supply)
+    } else {
+        supply
+    }
+}
+
+// Per-wallet limits for the round: minimum single purchase and maximum per wallet
+pub fn check_wallet_limits(round: &Round, buyer_record: &BuyerRecord, round_index: usize, amount: u64) -> Result<()> {
+    require!(amount >= round.min_purchase, ErrorCode::BelowMinimumPurchase);
+
+    let bought = buyer_record.tokens_per_round.get(round_index).copied().unwrap_or(0);
+    require!(amount <= round.wallet_allowance(bought), ErrorCode::WalletLimitExceeded);
+
+    Ok(())
+}
+
+// Proof that the buyer is on the round allowlist with `allocation` base units
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
+pub struct AllowlistProof {
+    pub allocation: u64,
+    pub proof: Vec<[u8; 32]>,
+}
+
+// How much more `buyer` may buy under the round allowlist (unlimited for open rounds)
+pub fn check_allowlist(
+    round: &Round,
+    buyer: &Pubkey,
+    bought: u64,
+    allowlist: Option<&AllowlistProof>,
+) -> Result<u64> {
+    if !round.has_allowlist() {
+        return Ok(u64::MAX);
     }
+
+    let allowlist = allowlist.ok_or(ErrorCode::NotAllowlisted)?;
+    require!(
+        verify_merkle_proof(
+            &round.allowlist_root,
+            round.allowlist_leaf_version,
+            buyer,
+            allowlist.allocation,
+            &allowlist.proof,
+        ),
+        ErrorCode::NotAllowlisted
+    );
+
+    Ok(allowlist.allocation.saturating_sub(bought))
+}
+
+// How the buyer sized the purchase. Token amounts are in base units of the sale
+// mint, so 12.5 tokens of a 6-decimal mint is 12_500_000.
+#[derive(Clone, Copy, Debug)]
+pub enum PurchaseSize {
+    // Buy exactly `amount` base units, paying at most `max_total_payment`
+    ExactTokens { amount: u64, max_total_payment: u64 },
+    // Spend at most `payment`, receiving at least `min_tokens_out` base units
+    ExactPayment { payment: u64, min_tokens_out: u64 },
+}
+
+// Price of a purchase in the payment currency
+#[derive(Clone, Copy, Debug)]
+pub struct Quote {
+    pub eurc_per_token: u64,
+    pub price_per_token: u64, // Per whole token, in the payment currency
+    pub total: u64,           // Rounded up to the next unit of the payment currency
+}
+
+fn total_for(amount: u64, price_per_token: u64, decimals: u8) -> Result<u64> {
+    curve::total_cost(amount, price_per_token, decimals).ok_or(error!(ErrorCode::Overflow))
+}
+
+pub fn quote_token(
+    amount: u64,
+    decimals: u8,
+    params: &PricingParams,
+    round: &Round,
+    currency: &AcceptedCurrency,
+    rates: CurrencyRates,
+) -> Result<Quote> {
+    let eurc_per_token = get_stablecoin_price_per_token(amount, decimals, params, round)?;
+    let price_per_token = convert_eurc_price_to_currency(eurc_per_token, currency.decimals, rates)?;
+    let total = total_for(amount, price_per_token, decimals)?;
+    Ok(Quote { eurc_per_token, price_per_token, total })
 }
 
-pub fn update_round_tokens_sold(config: &mut Config, round: Round, amount: u64) -> Result<()> {
-    match round {
-        Round::First => {
-            config.round1_tokens_sold = config.round1_tokens_sold
-                .checked_add(amount)
-                .ok_or(ErrorCode::Overflow)?;
+pub fn quote_sol(
+    amount: u64,
+    decimals: u8,
+    params: &PricingParams,
+    round: &Round,
+    eur_usd_rate: u64,
+    sol_usd_rate: u64,
+) -> Result<Quote> {
+    let eurc_per_token = get_stablecoin_price_per_token(amount, decimals, params, round)?;
+    let sol_per_token = convert_eurc_price_to_sol(eurc_per_token, eur_usd_rate, sol_usd_rate)?;
+    let total = total_for(amount, sol_per_token, decimals)?;
+    Ok(Quote { eurc_per_token, price_per_token: sol_per_token, total })
+}
+
+// Largest base-unit amount in [0, max_amount] whose total cost fits in `budget`.
+// Binary search over the cost function. The result is always affordable; it is the
+// largest affordable amount whenever total cost grows with amount, which holds across
+// whole tokens as long as the curve's volume discount never outpaces the extra tokens
+// bought. Between whole tokens the rounded price can step down, so a fractional
+// result may stop short of the exact maximum.
+pub fn max_affordable_amount<F>(budget: u64, max_amount: u64, cost: F) -> u64
+where
+    F: Fn(u64) -> Result<u64>,
+{
+    let affordable = |amount: u64| cost(amount).map(|total| total <= budget).unwrap_or(false);
+
+    let (mut low, mut high) = (0u64, max_amount);
+    while low < high {
+        let mid = low + (high - low).div_ceil(2);
+        if affordable(mid) {
+            low = mid;
+        } else {
+            high = mid - 1;
         }
-        Round::Second => {
-            config.round2_tokens_sold = config.round2_tokens_sold
-                .checked_add(amount)
-                .ok_or(ErrorCode::Overflow)?;
+    }
+    low
+}
+
+// Turn the buyer's request into a token amount and total payment, enforcing slippage bounds
+pub fn resolve_purchase<F>(size: PurchaseSize, max_amount: u64, cost: F) -> Result<(u64, u64)>
+where
+    F: Fn(u64) -> Result<u64>,
+{
+    match size {
+        PurchaseSize::ExactTokens { amount, max_total_payment } => {
+            require!(amount > 0, ErrorCode::InvalidAmount);
+            let total = cost(amount)?;
+            require!(total <= max_total_payment, ErrorCode::SlippageExceeded);
+            Ok((amount, total))
         }
-        Round::Third => {
-            config.round3_tokens_sold = config.round3_tokens_sold
-                .checked_add(amount)
-                .ok_or(ErrorCode::Overflow)?;
+        PurchaseSize::ExactPayment { payment, min_tokens_out } => {
+            require!(payment > 0, ErrorCode::InvalidAmount);
+            let amount = max_affordable_amount(payment, max_amount, &cost);
+            require!(amount > 0, ErrorCode::InsufficientPayment);
+            require!(amount >= min_tokens_out, ErrorCode::SlippageExceeded);
+            Ok((amount, cost(amount)?))
         }
     }
+}
+
+pub fn update_round_tokens_sold(schedule: &mut RoundSchedule, round_index: usize, amount: u64) -> Result<()> {
+    let round = schedule.rounds.get_mut(round_index).ok_or(ErrorCode::RoundNotFound)?;
+    round.tokens_sold = round.tokens_sold
+        .checked_add(amount)
+        .ok_or(ErrorCode::Overflow)?;
     Ok(())
 }
 
-// Main sale functions - UPDATED TO USE MOCK PRICES
-pub fn buy(ctx: Context<Buy>, amount: u64) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    require!(config.initialized, ErrorCode::NotInitialized);
-    require!(!config.paused, ErrorCode::SalePaused);
-    require!(amount > 0, ErrorCode::InvalidAmount);
+// Main sale functions
+pub fn buy(
+    ctx: Context<Buy>,
+    amount: u64,
+    max_total_payment: u64,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    process_sol_purchase(ctx, PurchaseSize::ExactTokens { amount, max_total_payment }, allowlist)
+}
 
-    let current_round = get_current_round(config)?;
+pub fn buy_exact_in(
+    ctx: Context<Buy>,
+    payment: u64,
+    min_tokens_out: u64,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    process_sol_purchase(ctx, PurchaseSize::ExactPayment { payment, min_tokens_out }, allowlist)
+}
 
-    // Check round limit
-    check_round_limit(config, amount)?;
+fn process_sol_purchase(ctx: Context<Buy>, size: PurchaseSize, allowlist: Option<AllowlistProof>) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.initialized, ErrorCode::NotInitialized);
+    require!(!config.paused, ErrorCode::SalePaused);
+    kyc::verify_attestation(&ctx.accounts.instructions_sysvar, &config.kyc, &ctx.accounts.buyer.key())?;
 
-    // Get your existing EURC price per token with tiers and rounds
-    let eurc_per_token = get_stablecoin_price_per_token(amount, current_round);
+    let schedule = &mut ctx.accounts.round_schedule;
+    let round_index = get_current_round(schedule)?;
+    let round_number = round_index + 1;
+    let round = schedule.rounds[round_index];
+    let params = &ctx.accounts.pricing_params;
+    let decimals = ctx.accounts.mint.decimals;
 
-    // Use MOCK prices instead of Pyth
-    let eur_usd_rate = get_mock_price(&ctx.accounts.pyth_eur_usd_account)?;
-    let sol_usd_rate = get_mock_price(&ctx.accounts.pyth_sol_usd_account)?;
-    let sol_per_token = convert_eurc_price_to_sol(eurc_per_token, eur_usd_rate, sol_usd_rate)?;
+    // Validated oracle prices
+    let eur_usd_rate = oracle::get_price(&ctx.accounts.pyth_eur_usd_account, &config.oracle, Feed::EurUsd)?;
+    let sol_usd_rate = oracle::get_price(&ctx.accounts.pyth_sol_usd_account, &config.oracle, Feed::SolUsd)?;
+
+    // Referral reward - bonus tokens, or a share of the payment sent to the referrer instead of the vault
+    let referrer = resolve_referrer(
+        &config.referral,
+        &ctx.accounts.buyer_record,
+        &ctx.accounts.buyer.key(),
+        ctx.accounts.referral_record.as_deref(),
+    )?;
+
+    // Resolve token amount and SOL cost (slippage bounds checked here)
+    let bought = ctx.accounts.buyer_record.tokens_per_round[round_index];
+    let allowlist_allowance = check_allowlist(&round, &ctx.accounts.buyer.key(), bought, allowlist.as_ref())?;
+    let max_amount = purchasable_supply(&config.referral, referrer.is_some(), &config.caps, schedule, &round)
+        .min(round.wallet_allowance(bought))
+        .min(allowlist_allowance);
+    let (amount, _) = resolve_purchase(size, max_amount, |amount| {
+        quote_sol(amount, decimals, params, &round, eur_usd_rate, sol_usd_rate).map(|quote| quote.total)
+    })?;
+
+    let quote = quote_sol(amount, decimals, params, &round, eur_usd_rate, sol_usd_rate)?;
+    let eurc_per_token = quote.eurc_per_token;
+    let sol_per_token = quote.price_per_token;
+    let total_price = quote.total;
+    let (bonus_tokens, payment_share) = match referrer {
+        Some(_) => config.referral.rewards(amount, total_price),
+        None => (0, 0),
+    };
+    let issued = amount.checked_add(bonus_tokens).ok_or(ErrorCode::Overflow)?;
 
-    let total_price = amount
-        .checked_mul(sol_per_token)
-        .ok_or(ErrorCode::Overflow)?;
+    // Check round and wallet limits - bonus tokens count against the round and hard cap
+    check_round_limit(schedule, round_index, issued)?;
+    check_hard_cap(&config.caps, schedule, issued)?;
+    check_wallet_limits(&round, &ctx.accounts.buyer_record, round_index, amount)?;
+    require!(amount <= allowlist_allowance, ErrorCode::AllowlistAllocationExceeded);
 
-    msg!("🛒 Buying {} tokens for {} SOL ({} SOL/token) - Round: {:?}",
-         amount, total_price, sol_per_token, current_round);
-    msg!("💰 Using mock prices - EUR/USD: {}, SOL/USD: {}", eur_usd_rate, sol_usd_rate);
+    msg!("🛒 Buying {} base units for {} SOL ({} SOL/token) - Round: {}",
+         amount, total_price, sol_per_token, round_number);
+    msg!("💰 Oracle prices - EUR/USD: {}, SOL/USD: {}", eur_usd_rate, sol_usd_rate);
 
-    // Transfer SOL
+    // Transfer SOL to the program vault, less any referral payment share
     let cpi_context = CpiContext::new(
         ctx.accounts.system_program.to_account_info(),
         anchor_lang::system_program::Transfer {
             from: ctx.accounts.buyer.to_account_info(),
-            to: ctx.accounts.treasury.to_account_info(),
+            to: ctx.accounts.vault.to_account_info(),
         },
     );
-    anchor_lang::system_program::transfer(cpi_context, total_price)?;
-
-    // Mint tokens with PDA signing
-    let seeds = b"mint";
-    let bump = ctx.bumps.mint_authority_pda;
-    let signer_seeds: &[&[&[u8]]] = &[&[seeds, &[bump]]];
-
-    let cpi_accounts = MintTo {
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.buyer_ata.to_account_info(),
-        authority: ctx.accounts.mint_authority_pda.to_account_info(),
-    };
-
-    let cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_2022_program.to_account_info(),
-        cpi_accounts,
-        signer_seeds,
-    );
-
-    let decimals = ctx.accounts.mint.decimals;
-    let base_units = amount
-        .checked_mul(10u64.pow(decimals as u32))
-        .ok_or(ErrorCode::Overflow)?;
+    anchor_lang::system_program::transfer(cpi_context, total_price - payment_share)?;
+
+    // Mint now, or credit the buyer's vesting schedule when the round vests
+    if round.has_vesting() {
+        let vesting = ctx.accounts.vesting.as_mut().ok_or(ErrorCode::VestingAccountRequired)?;
+        vesting.buyer = ctx.accounts.buyer.key();
+        vesting.bump = ctx.bumps.vesting.ok_or(ErrorCode::VestingAccountRequired)?;
+        vesting.credit(&round, amount)?;
+        msg!("🔒 {} base units vesting after round end ({}s cliff + {}s)",
+             amount, round.vesting_cliff, round.vesting_duration);
+    } else {
+        // Mint tokens with PDA signing
+        let seeds = b"mint";
+        let bump = ctx.bumps.mint_authority_pda;
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds, &[bump]]];
+
+        let cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.buyer_ata.to_account_info(),
+            authority: ctx.accounts.mint_authority_pda.to_account_info(),
+        };
+
+        let cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_2022_program.to_account_info(),
+            cpi_accounts,
+            signer_seeds,
+        );
+
+        token22::mint_to(cpi_ctx, amount)?;
+    }
 
-    token22::mint_to(cpi_ctx, base_units)?;
+    // Pay the referral reward
+    if let Some(referrer) = referrer {
+        if bonus_tokens > 0 {
+            let to = referrer_token_account(
+                ctx.accounts.referrer_token_account.as_ref(),
+                &referrer,
+                &ctx.accounts.mint.key(),
+            )?;
+            mint_referral_bonus(
+                &ctx.accounts.mint,
+                to,
+                &ctx.accounts.mint_authority_pda,
+                &ctx.accounts.token_2022_program,
+                ctx.bumps.mint_authority_pda,
+                bonus_tokens,
+            )?;
+        }
+        if payment_share > 0 {
+            let cpi_context = CpiContext::new(
+                ctx.accounts.system_program.to_account_info(),
+                anchor_lang::system_program::Transfer {
+                    from: ctx.accounts.buyer.to_account_info(),
+                    to: referrer_wallet(ctx.accounts.referrer.as_ref(), &referrer)?,
+                },
+            );
+            anchor_lang::system_program::transfer(cpi_context, payment_share)?;
+        }
+        msg!("🤝 Referrer {} rewarded: {} bonus base units, {} SOL", referrer, bonus_tokens, payment_share);
+    }
 
-    // Update round tokens sold
-    update_round_tokens_sold(config, current_round, amount)?;
+    // Update round tokens sold, including the referral bonus
+    update_round_tokens_sold(schedule, round_index, issued)?;
+
+    // Update the buyer's purchase ledger
+    let buyer_record = &mut ctx.accounts.buyer_record;
+    buyer_record.buyer = ctx.accounts.buyer.key();
+    buyer_record.bump = ctx.bumps.buyer_record;
+    let first_purchase = buyer_record.purchase_count == 0;
+    buyer_record.record_purchase(round_index, amount, PaymentCurrency::Sol, total_price, Clock::get()?.unix_timestamp)?;
+    if let Some(referrer) = referrer {
+        buyer_record.referrer = referrer;
+        if let Some(record) = ctx.accounts.referral_record.as_mut() {
+            record.record_referral(first_purchase, amount, bonus_tokens, PaymentCurrency::Sol, payment_share)?;
+        }
+    }
 
     emit!(TokenPurchasedWithSol {
         buyer: ctx.accounts.buyer.key(),
@@ -441,227 +690,408 @@ pub fn buy(ctx: Context<Buy>, amount: u64) -> Result<()> {
         equivalent_eurc_price: eurc_per_token,
         timestamp: Clock::get()?.unix_timestamp,
         payment_method: "SOL".to_string(),
-        tier: get_tier_name(amount),
-        round: current_round as u8,
+        tier: get_tier_name(amount, decimals)?,
+        round: round_number as u8,
+        referrer,
+        referral_reward: bonus_tokens + payment_share,
     });
 
-    let remaining = TOKENS_PER_ROUND - get_round_tokens_sold(config, current_round);
-    msg!("✅ SOL purchase successful! Round: {:?}, Remaining: {} tokens", current_round, remaining);
+    let remaining = schedule.rounds[round_index].remaining_tokens();
+    msg!("✅ SOL purchase successful! Round: {}, Remaining: {} base units", round_number, remaining);
     Ok(())
 }
 
-pub fn buy_with_eurc(ctx: Context<BuyWithEurc>, amount: u64) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    require!(config.initialized, ErrorCode::NotInitialized);
-    require!(!config.paused, ErrorCode::SalePaused);
-    require!(amount > 0, ErrorCode::InvalidAmount);
-
-    let current_round = get_current_round(config)?;
-
-    // Check round limit
-    check_round_limit(config, amount)?;
+pub fn buy_with_token<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyWithToken<'info>>,
+    amount: u64,
+    max_total_payment: u64,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    process_token_purchase(ctx, PurchaseSize::ExactTokens { amount, max_total_payment }, allowlist)
+}
 
-    // Calculate EURC price based on tiers and current round
-    let eurc_per_token = get_stablecoin_price_per_token(amount, current_round);
-    let total_eurc_price = amount
-        .checked_mul(eurc_per_token)
-        .ok_or(ErrorCode::Overflow)?;
+pub fn buy_with_token_exact_in<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyWithToken<'info>>,
+    payment: u64,
+    min_tokens_out: u64,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    process_token_purchase(ctx, PurchaseSize::ExactPayment { payment, min_tokens_out }, allowlist)
+}
 
-    msg!("🛒 Buying {} tokens for {} EURC ({} EURC/token) - Round: {:?}",
-         amount, total_eurc_price, eurc_per_token, current_round);
+// Token payment for a purchase. With a transfer-fee mint the vault's part is grossed up
+// so the vault nets the quoted price; the referrer bears the fee on its payment share.
+#[derive(Clone, Copy, Debug)]
+struct TokenPayment {
+    quote: Quote,
+    bonus_tokens: u64,
+    payment_share: u64,
+    vault_amount: u64, // Net amount the vault must receive
+    vault_gross: u64,  // Sent to the vault, including the transfer fee
+}
 
-    // Transfer EURC from buyer to treasury
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.buyer_eurc_ata.to_account_info(),
-        to: ctx.accounts.treasury_eurc_ata.to_account_info(),
-        authority: ctx.accounts.buyer.to_account_info(),
-    };
+impl TokenPayment {
+    fn buyer_total(&self) -> Result<u64> {
+        self.vault_gross.checked_add(self.payment_share).ok_or(error!(ErrorCode::Overflow))
+    }
+}
 
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
+// Transfer-hook accounts for the payment mint are passed as remaining accounts
+fn process_token_purchase<'info>(
+    ctx: Context<'_, '_, 'info, 'info, BuyWithToken<'info>>,
+    size: PurchaseSize,
+    allowlist: Option<AllowlistProof>,
+) -> Result<()> {
+    let config = &ctx.accounts.config;
+    require!(config.initialized, ErrorCode::NotInitialized);
+    require!(!config.paused, ErrorCode::SalePaused);
+    kyc::verify_attestation(&ctx.accounts.instructions_sysvar, &config.kyc, &ctx.accounts.buyer.key())?;
+
+    // Payment currency must be registered and enabled
+    let payment_mint = &ctx.accounts.payment_mint;
+    let (currency_index, currency) = ctx.accounts.payment_registry.currency(&payment_mint.key())?;
+    require!(currency.enabled, ErrorCode::CurrencyDisabled);
+    require_keys_eq!(
+        ctx.accounts.payment_token_program.key(),
+        currency.token_program,
+        ErrorCode::CurrencyNotAccepted
     );
 
-    token::transfer(cpi_ctx, total_eurc_price)?;
-
-    // Mint tokens
-    let seeds = b"mint";
-    let bump = ctx.bumps.mint_authority_pda;
-    let signer_seeds: &[&[&[u8]]] = &[&[seeds, &[bump]]];
+    let schedule = &mut ctx.accounts.round_schedule;
+    let round_index = get_current_round(schedule)?;
+    let round_number = round_index + 1;
+    let round = schedule.rounds[round_index];
+    let params = &ctx.accounts.pricing_params;
+    let decimals = ctx.accounts.mint.decimals;
 
-    let mint_cpi_accounts = MintTo {
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.buyer_ata.to_account_info(),
-        authority: ctx.accounts.mint_authority_pda.to_account_info(),
+    // Validated oracle prices for the currency's price source
+    let rates = get_currency_rates(
+        &currency,
+        &config.oracle,
+        &ctx.accounts.pyth_eur_usd_account,
+        ctx.accounts.pyth_currency_usd_account.as_ref().map(|account| account.as_ref()),
+    )?;
+
+    // Referral reward - bonus tokens, or a share of the payment sent to the referrer instead of the vault
+    let referrer = resolve_referrer(
+        &config.referral,
+        &ctx.accounts.buyer_record,
+        &ctx.accounts.buyer.key(),
+        ctx.accounts.referral_record.as_deref(),
+    )?;
+
+    let transfer_fee = token_transfer::current_transfer_fee(&payment_mint.to_account_info())?;
+    let token_payment = |amount: u64| -> Result<TokenPayment> {
+        let quote = quote_token(amount, decimals, params, &round, &currency, rates)?;
+        let (bonus_tokens, payment_share) = match referrer {
+            Some(_) => config.referral.rewards(amount, quote.total),
+            None => (0, 0),
+        };
+        let vault_amount = quote.total - payment_share;
+        let vault_gross = token_transfer::gross_up(transfer_fee.as_ref(), vault_amount)?;
+        Ok(TokenPayment { quote, bonus_tokens, payment_share, vault_amount, vault_gross })
     };
 
-    let mint_cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_2022_program.to_account_info(),
-        mint_cpi_accounts,
-        signer_seeds,
-    );
-
-    let decimals = ctx.accounts.mint.decimals;
-    let base_units = amount
-        .checked_mul(10u64.pow(decimals as u32))
-        .ok_or(ErrorCode::Overflow)?;
+    // Resolve token amount and cost, including transfer fees (slippage bounds checked here)
+    let bought = ctx.accounts.buyer_record.tokens_per_round[round_index];
+    let allowlist_allowance = check_allowlist(&round, &ctx.accounts.buyer.key(), bought, allowlist.as_ref())?;
+    let max_amount = purchasable_supply(&config.referral, referrer.is_some(), &config.caps, schedule, &round)
+        .min(round.wallet_allowance(bought))
+        .min(allowlist_allowance);
+    let (amount, _) = resolve_purchase(size, max_amount, |amount| {
+        token_payment(amount).and_then(|payment| payment.buyer_total())
+    })?;
+
+    let payment = token_payment(amount)?;
+    let eurc_per_token = payment.quote.eurc_per_token;
+    let price_per_token = payment.quote.price_per_token;
+    let total_price = payment.quote.total;
+    let (bonus_tokens, payment_share) = (payment.bonus_tokens, payment.payment_share);
+    let issued = amount.checked_add(bonus_tokens).ok_or(ErrorCode::Overflow)?;
+
+    // Check round and wallet limits - bonus tokens count against the round and hard cap
+    check_round_limit(schedule, round_index, issued)?;
+    check_hard_cap(&config.caps, schedule, issued)?;
+    check_wallet_limits(&round, &ctx.accounts.buyer_record, round_index, amount)?;
+    require!(amount <= allowlist_allowance, ErrorCode::AllowlistAllocationExceeded);
+
+    msg!("🛒 Buying {} base units for {} of {} ({}/token) - Round: {}",
+         amount, total_price, payment_mint.key(), price_per_token, round_number);
+    msg!("💰 Oracle rates - EUR/USD: {}, currency/USD: {}", rates.eur_usd, rates.currency_usd);
+
+    // Transfer the payment from buyer to the program vault, less any referral payment share.
+    // Only what actually arrives is credited to the buyer.
+    let vault_before = ctx.accounts.vault_payment_ata.amount;
+    token_transfer::transfer_checked(
+        &ctx.accounts.payment_token_program.to_account_info(),
+        ctx.accounts.buyer_payment_ata.to_account_info(),
+        payment_mint.to_account_info(),
+        ctx.accounts.vault_payment_ata.to_account_info(),
+        ctx.accounts.buyer.to_account_info(),
+        ctx.remaining_accounts,
+        payment.vault_gross,
+        currency.decimals,
+        &[],
+    )?;
+    ctx.accounts.vault_payment_ata.reload()?;
+    let received = ctx.accounts.vault_payment_ata.amount.saturating_sub(vault_before);
+    require!(received >= payment.vault_amount, ErrorCode::InsufficientPayment);
+
+    // Mint now, or credit the buyer's vesting schedule when the round vests
+    if round.has_vesting() {
+        let vesting = ctx.accounts.vesting.as_mut().ok_or(ErrorCode::VestingAccountRequired)?;
+        vesting.buyer = ctx.accounts.buyer.key();
+        vesting.bump = ctx.bumps.vesting.ok_or(ErrorCode::VestingAccountRequired)?;
+        vesting.credit(&round, amount)?;
+        msg!("🔒 {} base units vesting after round end ({}s cliff + {}s)",
+             amount, round.vesting_cliff, round.vesting_duration);
+    } else {
+        // Mint tokens
+        let seeds = b"mint";
+        let bump = ctx.bumps.mint_authority_pda;
+        let signer_seeds: &[&[&[u8]]] = &[&[seeds, &[bump]]];
+
+        let mint_cpi_accounts = MintTo {
+            mint: ctx.accounts.mint.to_account_info(),
+            to: ctx.accounts.buyer_ata.to_account_info(),
+            authority: ctx.accounts.mint_authority_pda.to_account_info(),
+        };
+
+        let mint_cpi_ctx = CpiContext::new_with_signer(
+            ctx.accounts.token_2022_program.to_account_info(),
+            mint_cpi_accounts,
+            signer_seeds,
+        );
+
+        token22::mint_to(mint_cpi_ctx, amount)?;
+    }
 
-    token22::mint_to(mint_cpi_ctx, base_units)?;
+    // Pay the referral reward
+    if let Some(referrer) = referrer {
+        if bonus_tokens > 0 {
+            let to = referrer_token_account(
+                ctx.accounts.referrer_token_account.as_ref(),
+                &referrer,
+                &ctx.accounts.mint.key(),
+            )?;
+            mint_referral_bonus(
+                &ctx.accounts.mint,
+                to,
+                &ctx.accounts.mint_authority_pda,
+                &ctx.accounts.token_2022_program,
+                ctx.bumps.mint_authority_pda,
+                bonus_tokens,
+            )?;
+        }
+        if payment_share > 0 {
+            token_transfer::transfer_checked(
+                &ctx.accounts.payment_token_program.to_account_info(),
+                ctx.accounts.buyer_payment_ata.to_account_info(),
+                payment_mint.to_account_info(),
+                referrer_token_account(ctx.accounts.referrer_token_account.as_ref(), &referrer, &payment_mint.key())?,
+                ctx.accounts.buyer.to_account_info(),
+                ctx.remaining_accounts,
+                payment_share,
+                currency.decimals,
+                &[],
+            )?;
+        }
+        msg!("🤝 Referrer {} rewarded: {} bonus base units, {} payment base units", referrer, bonus_tokens, payment_share);
+    }
 
-    // Update round tokens sold
-    update_round_tokens_sold(config, current_round, amount)?;
+    // Update round tokens sold, including the referral bonus
+    update_round_tokens_sold(schedule, round_index, issued)?;
+
+    // Update the buyer's purchase ledger
+    let payment_currency = PaymentCurrency::Token(currency_index as u8);
+    let buyer_record = &mut ctx.accounts.buyer_record;
+    buyer_record.buyer = ctx.accounts.buyer.key();
+    buyer_record.bump = ctx.bumps.buyer_record;
+    let first_purchase = buyer_record.purchase_count == 0;
+    let paid = received.checked_add(payment_share).ok_or(ErrorCode::Overflow)?;
+    buyer_record.record_purchase(round_index, amount, payment_currency, paid, Clock::get()?.unix_timestamp)?;
+    if let Some(referrer) = referrer {
+        buyer_record.referrer = referrer;
+        if let Some(record) = ctx.accounts.referral_record.as_mut() {
+            record.record_referral(first_purchase, amount, bonus_tokens, payment_currency, payment_share)?;
+        }
+    }
 
     // EMIT EVENT
-    emit!(TokenPurchasedWithEurc {
+    emit!(TokenPurchasedWithToken {
         buyer: ctx.accounts.buyer.key(),
         token_amount: amount,
-        eurc_amount: total_eurc_price,
-        eurc_per_token: eurc_per_token,
+        payment_mint: payment_mint.key(),
+        payment_amount: payment.buyer_total()?,
+        transfer_fee: payment.vault_gross.saturating_sub(received),
+        price_per_token,
+        eur_usd_rate: rates.eur_usd,
+        currency_usd_rate: rates.currency_usd,
+        equivalent_eurc_price: eurc_per_token,
         timestamp: Clock::get()?.unix_timestamp,
-        payment_method: "EURC".to_string(),
-        tier: get_tier_name(amount),
-        round: current_round as u8,
+        tier: get_tier_name(amount, decimals)?,
+        round: round_number as u8,
+        referrer,
+        referral_reward: bonus_tokens + payment_share,
     });
 
-    let remaining = TOKENS_PER_ROUND - get_round_tokens_sold(config, current_round);
-    msg!("✅ EURC purchase successful! {} tokens at round: {:?}, tier: {}, remaining: {} tokens",
-         amount, current_round, get_tier_name(amount), remaining);
+    let remaining = schedule.rounds[round_index].remaining_tokens();
+    msg!("✅ Token purchase successful! {} base units at round: {}, tier: {}, remaining: {} base units",
+         amount, round_number, get_tier_name(amount, decimals)?, remaining);
     Ok(())
 }
 
-pub fn buy_with_usdc(ctx: Context<BuyWithUsdc>, amount: u64) -> Result<()> {
-    let config = &mut ctx.accounts.config;
-    require!(config.initialized, ErrorCode::NotInitialized);
-    require!(!config.paused, ErrorCode::SalePaused);
-    require!(amount > 0, ErrorCode::InvalidAmount);
-
-    let current_round = get_current_round(config)?;
-
-    // Check round limit
-    check_round_limit(config, amount)?;
-
-    // Get your existing EURC price per token with tiers and rounds
-    let eurc_per_token = get_stablecoin_price_per_token(amount, current_round);
-
-    // Use MOCK price for EUR/USD conversion
-    let eur_usd_rate = get_mock_price(&ctx.accounts.pyth_eur_usd_account)?;
-    let usdc_per_token = convert_eurc_price_to_usdc(eurc_per_token, eur_usd_rate)?;
-
-    let total_usdc_price = amount
-        .checked_mul(usdc_per_token)
-        .ok_or(ErrorCode::Overflow)?;
+pub fn get_round_info(ctx: Context<GetRoundInfo>) -> Result<RoundInfo> {
+    let schedule = &ctx.accounts.round_schedule;
+    let round_index = get_current_round(schedule)?;
+    let round = &schedule.rounds[round_index];
 
-    msg!("🛒 Buying {} tokens for {} USDC ({} USDC/token) - Round: {:?}",
-         amount, total_usdc_price, usdc_per_token, current_round);
-    msg!("💰 Using mock EUR/USD rate: {}", eur_usd_rate);
+    Ok(RoundInfo {
+        current_round: (round_index + 1) as u8,
+        round_start_time: round.start_time,
+        round_end_time: round.end_time,
+        sale_start_time: schedule.sale_start_time(),
+        total_duration: schedule.sale_end_time() - schedule.sale_start_time(),
+        round_tokens_sold: round.tokens_sold,
+        remaining_tokens: round.remaining_tokens(),
+        round_token_cap: round.token_cap,
+        discount_bps: round.discount_bps,
+        vesting_cliff: round.vesting_cliff,
+        vesting_duration: round.vesting_duration,
+        min_purchase: round.min_purchase,
+        max_per_wallet: round.max_per_wallet,
+        allowlist_root: round.allowlist_root,
+        allowlist_leaf_version: round.allowlist_leaf_version,
+        total_rounds: schedule.rounds.len() as u8,
+    })
+}
 
-    // Transfer USDC from buyer to treasury
-    let cpi_accounts = Transfer {
-        from: ctx.accounts.buyer_usdc_ata.to_account_info(),
-        to: ctx.accounts.treasury_usdc_ata.to_account_info(),
-        authority: ctx.accounts.buyer.to_account_info(),
-    };
+pub fn get_all_rounds_info(ctx: Context<GetRoundInfo>) -> Result<AllRoundsInfo> {
+    let schedule = &ctx.accounts.round_schedule;
+
+    let rounds = schedule
+        .rounds
+        .iter()
+        .map(|round| RoundDetails {
+            start_time: round.start_time,
+            end_time: round.end_time,
+            discount_bps: round.discount_bps,
+            vesting_cliff: round.vesting_cliff,
+            vesting_duration: round.vesting_duration,
+            min_purchase: round.min_purchase,
+            max_per_wallet: round.max_per_wallet,
+            allowlist_root: round.allowlist_root,
+            allowlist_leaf_version: round.allowlist_leaf_version,
+            tokens_sold: round.tokens_sold,
+            remaining: round.remaining_tokens(),
+            total: round.token_cap,
+        })
+        .collect();
 
-    let cpi_ctx = CpiContext::new(
-        ctx.accounts.token_program.to_account_info(),
-        cpi_accounts,
-    );
+    Ok(AllRoundsInfo {
+        rounds,
+        total_tokens_sold: schedule.total_tokens_sold(),
+        total_tokens: schedule.total_token_cap(),
+    })
+}
 
-    token::transfer(cpi_ctx, total_usdc_price)?;
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::assert_error;
+    use proptest::prelude::*;
 
-    // Mint tokens
-    let seeds = b"mint";
-    let bump = ctx.bumps.mint_authority_pda;
-    let signer_seeds: &[&[&[u8]]] = &[&[seeds, &[bump]]];
+    // 3 units per base unit, erroring past 1_000
+    fn linear_cost(amount: u64) -> Result<u64> {
+        require!(amount <= 1_000, ErrorCode::Overflow);
+        Ok(amount * 3)
+    }
 
-    let mint_cpi_accounts = MintTo {
-        mint: ctx.accounts.mint.to_account_info(),
-        to: ctx.accounts.buyer_ata.to_account_info(),
-        authority: ctx.accounts.mint_authority_pda.to_account_info(),
-    };
+    fn default_params() -> PricingParams {
+        PricingParams {
+            price_max: 290_000,
+            price_min: 180_000,
+            midpoint: 10_000,
+            steepness: 300_000,
+            last_updated: 0,
+            bump: 255,
+        }
+    }
 
-    let mint_cpi_ctx = CpiContext::new_with_signer(
-        ctx.accounts.token_2022_program.to_account_info(),
-        mint_cpi_accounts,
-        signer_seeds,
-    );
+    fn round(discount_bps: u16) -> Round {
+        Round::from(RoundParams {
+            start_time: 0,
+            end_time: 1,
+            token_cap: u64::MAX,
+            discount_bps,
+            vesting_cliff: 0,
+            vesting_duration: 0,
+            min_purchase: 0,
+            max_per_wallet: 0,
+        })
+    }
 
-    let decimals = ctx.accounts.mint.decimals;
-    let base_units = amount
-        .checked_mul(10u64.pow(decimals as u32))
-        .ok_or(ErrorCode::Overflow)?;
+    // Total EURC cost of `amount` base units on the real curve
+    fn curve_cost(amount: u64, decimals: u8, discount_bps: u16) -> Result<u64> {
+        let price = get_stablecoin_price_per_token(amount, decimals, &default_params(), &round(discount_bps))?;
+        total_for(amount, price, decimals)
+    }
 
-    token22::mint_to(mint_cpi_ctx, base_units)?;
+    #[test]
+    fn budget_on_a_boundary_buys_exactly() {
+        assert_eq!(max_affordable_amount(30, 1_000, linear_cost), 10);
+        assert_eq!(max_affordable_amount(29, 1_000, linear_cost), 9);
+        assert_eq!(max_affordable_amount(31, 1_000, linear_cost), 10);
+        assert_eq!(max_affordable_amount(3_000, 1_000, linear_cost), 1_000);
+    }
 
-    // Update round tokens sold
-    update_round_tokens_sold(config, current_round, amount)?;
+    #[test]
+    fn max_amount_bounds_the_search() {
+        assert_eq!(max_affordable_amount(u64::MAX, 0, linear_cost), 0);
+        assert_eq!(max_affordable_amount(u64::MAX, 7, linear_cost), 7);
+        // Errors past 1_000 count as unaffordable
+        assert_eq!(max_affordable_amount(u64::MAX, u64::MAX, linear_cost), 1_000);
+    }
 
-    // EMIT EVENT
-    emit!(TokenPurchasedWithUsdc {
-        buyer: ctx.accounts.buyer.key(),
-        token_amount: amount,
-        usdc_amount: total_usdc_price,
-        usdc_per_token: usdc_per_token,
-        eur_usd_rate: eur_usd_rate,
-        equivalent_eurc_price: eurc_per_token,
-        timestamp: Clock::get()?.unix_timestamp,
-        payment_method: "USDC".to_string(),
-        tier: get_tier_name(amount),
-        round: current_round as u8,
-    });
+    #[test]
+    fn unaffordable_first_unit_buys_nothing() {
+        assert_eq!(max_affordable_amount(2, 1_000, linear_cost), 0);
+        assert_eq!(max_affordable_amount(0, 1_000, linear_cost), 0);
 
-    let remaining = TOKENS_PER_ROUND - get_round_tokens_sold(config, current_round);
-    msg!("✅ USDC purchase successful! {} tokens at round: {:?}, tier: {}, remaining: {} tokens", 
-         amount, current_round, get_tier_name(amount), remaining);
-    Ok(())
-}
+        let size = PurchaseSize::ExactPayment { payment: 2, min_tokens_out: 0 };
+        assert_error(resolve_purchase(size, 1_000, linear_cost), ErrorCode::InsufficientPayment);
+        let size = PurchaseSize::ExactPayment { payment: 30, min_tokens_out: 0 };
+        assert_error(resolve_purchase(size, 0, linear_cost), ErrorCode::InsufficientPayment);
+    }
 
-pub fn get_round_info(ctx: Context<GetRoundInfo>) -> Result<RoundInfo> {
-    let config = &ctx.accounts.config;
-    let current_round = get_current_round(config)?;
-    let current_time = Clock::get()?.unix_timestamp;
-    let round_duration: i64 = 30 * 24 * 60 * 60;
+    #[test]
+    fn exact_payment_respects_min_tokens_out() {
+        let size = PurchaseSize::ExactPayment { payment: 31, min_tokens_out: 10 };
+        assert_eq!(resolve_purchase(size, 1_000, linear_cost).unwrap(), (10, 30));
+        let size = PurchaseSize::ExactPayment { payment: 31, min_tokens_out: 11 };
+        assert_error(resolve_purchase(size, 1_000, linear_cost), ErrorCode::SlippageExceeded);
+    }
 
-    let round_start = config.sale_start_time + ((current_round as i64 - 1) * round_duration);
-    let round_end = round_start + round_duration;
-    let round_tokens_sold = get_round_tokens_sold(config, current_round);
-    let remaining_tokens = TOKENS_PER_ROUND.checked_sub(round_tokens_sold).unwrap_or(0);
+    proptest! {
+        // The per-token price is rounded to whole units, so between whole tokens the
+        // total can dip by up to one unit per token bought when the price steps down.
+        // Across whole tokens the curve's volume discount never outpaces the extra
+        // tokens, which is what the binary search relies on.
+        #[test]
+        fn cost_never_decreases_with_amount(tokens in 0u64..2_000_000, step in 1u64..1_000, decimals in 0u8..=9, discount_bps in 0u16..5_000) {
+            let unit = 10u64.pow(decimals as u32);
+            let smaller = curve_cost(tokens * unit, decimals, discount_bps).unwrap();
+            let larger = curve_cost((tokens + step) * unit, decimals, discount_bps).unwrap();
+            prop_assert!(larger >= smaller, "tokens={} step={} {} > {}", tokens, step, smaller, larger);
+        }
 
-    Ok(RoundInfo {
-        current_round: current_round as u8,
-        round_start_time: round_start,
-        round_end_time: round_end,
-        sale_start_time: config.sale_start_time,
-        total_duration: round_duration * 3,
-        round_tokens_sold,
-        remaining_tokens,
-        tokens_per_round: TOKENS_PER_ROUND,
-    })
+        #[test]
+        fn search_finds_the_largest_affordable_amount(budget in 0u64..10_000_000_000, max_amount in 0u64..100_000, discount_bps in 0u16..5_000) {
+            let cost = |amount| curve_cost(amount, 0, discount_bps);
+            let amount = max_affordable_amount(budget, max_amount, cost);
+            prop_assert!(amount <= max_amount);
+            prop_assert!(cost(amount).unwrap() <= budget);
+            prop_assert!(amount == max_amount || cost(amount + 1).unwrap() > budget);
+        }
+    }
 }
-
-pub fn get_all_rounds_info(ctx: Context<GetRoundInfo>) -> Result<AllRoundsInfo> {
-    let config = &ctx.accounts.config;
-
-    Ok(AllRoundsInfo {
-        round1: RoundDetails {
-            tokens_sold: config.round1_tokens_sold,
-            remaining: TOKENS_PER_ROUND.checked_sub(config.round1_tokens_sold).unwrap_or(0),
-            total: TOKENS_PER_ROUND,
-        },
-        round2: RoundDetails {
-            tokens_sold: config.round2_tokens_sold,
-            remaining: TOKENS_PER_ROUND.checked_sub(config.round2_tokens_sold).unwrap_or(0),
-            total: TOKENS_PER_ROUND,
-        },
-        round3: RoundDetails {
-            tokens_sold: config.round3_tokens_sold,
-            remaining: TOKENS_PER_ROUND.checked_sub(config.round3_tokens_sold).unwrap_or(0),
-            total: TOKENS_PER_ROUND,
-        },
-        total_tokens_sold: config.round1_tokens_sold
-            .checked_add(config.round2_tokens_sold)
-            .and_then(|sum| sum.checked_add(config.round3_tokens_sold))
-            .unwrap_or(0),
-        total_tokens: TOKENS_PER_ROUND * 3,
-    })
-}
\ No newline at end of file
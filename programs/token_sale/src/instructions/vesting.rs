@@ -0,0 +1,85 @@
+use anchor_lang::prelude::*;
+use anchor_spl::associated_token::AssociatedToken;
+use anchor_spl::token_2022::{self as token22, MintTo, Token2022};
+use anchor_spl::token_interface::{Mint, TokenAccount};
+use crate::state::{Config, VestingAccount, VestedTokensClaimed};
+use crate::error::ErrorCode;
+
+#[derive(Accounts)]
+pub struct ClaimVested<'info> {
+    #[account(
+        seeds = [b"token_sale_config"],
+        bump = config.bump,
+        constraint = config.initialized @ ErrorCode::NotInitialized
+    )]
+    pub config: Account<'info, Config>,
+
+    #[account(
+        mut,
+        seeds = [b"vesting", buyer.key().as_ref()],
+        bump = vesting.bump,
+        has_one = buyer @ ErrorCode::Unauthorized
+    )]
+    pub vesting: Account<'info, VestingAccount>,
+
+    #[account(mut)]
+    pub buyer: Signer<'info>,
+
+    #[account(mut, address = config.mint @ ErrorCode::InvalidMint)]
+    pub mint: InterfaceAccount<'info, Mint>,
+
+    /// CHECK: Mint authority PDA
+    #[account(seeds = [b"mint"], bump)]
+    pub mint_authority_pda: UncheckedAccount<'info>,
+
+    #[account(
+        init_if_needed,
+        payer = buyer,
+        associated_token::mint = mint,
+        associated_token::authority = buyer,
+        associated_token::token_program = token_2022_program,
+    )]
+    pub buyer_ata: InterfaceAccount<'info, TokenAccount>,
+
+    pub token_2022_program: Program<'info, Token2022>,
+    pub associated_token_program: Program<'info, AssociatedToken>,
+    pub system_program: Program<'info, System>,
+}
+
+// Mint everything that has unlocked since the last claim
+pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+    let now = Clock::get()?.unix_timestamp;
+    let vesting = &mut ctx.accounts.vesting;
+
+    let amount = vesting.claim(now)?;
+    require!(amount > 0, ErrorCode::NothingToClaim);
+    let remaining = vesting.total_unclaimed();
+
+    let seeds = b"mint";
+    let bump = ctx.bumps.mint_authority_pda;
+    let signer_seeds: &[&[&[u8]]] = &[&[seeds, &[bump]]];
+
+    let cpi_accounts = MintTo {
+        mint: ctx.accounts.mint.to_account_info(),
+        to: ctx.accounts.buyer_ata.to_account_info(),
+        authority: ctx.accounts.mint_authority_pda.to_account_info(),
+    };
+
+    let cpi_ctx = CpiContext::new_with_signer(
+        ctx.accounts.token_2022_program.to_account_info(),
+        cpi_accounts,
+        signer_seeds,
+    );
+
+    token22::mint_to(cpi_ctx, amount)?;
+
+    emit!(VestedTokensClaimed {
+        buyer: ctx.accounts.buyer.key(),
+        amount,
+        remaining,
+        timestamp: now,
+    });
+
+    msg!("🔓 Claimed {} vested base units, {} still vesting", amount, remaining);
+    Ok(())
+}
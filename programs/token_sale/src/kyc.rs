@@ -0,0 +1,252 @@
+use anchor_lang::prelude::*;
+use anchor_lang::solana_program::ed25519_program;
+use anchor_lang::solana_program::sysvar::instructions::{load_current_index_checked, load_instruction_at_checked};
+use crate::state::KycConfig;
+use crate::error::ErrorCode;
+
+// Attestation message signed by the KYC authority:
+//   buyer (32) || expiry (i64 LE, unix seconds) || jurisdiction (2, ISO 3166-1 alpha-2)
+pub const ATTESTATION_LEN: usize = 32 + 8 + 2;
+
+// Ed25519 program instruction layout: [num_signatures u8, padding u8] followed by
+// one 14-byte offsets block per signature
+const SIGNATURE_OFFSETS_START: usize = 2;
+const SIGNATURE_OFFSETS_LEN: usize = 14;
+const PUBKEY_LEN: usize = 32;
+const SIGNATURE_LEN: usize = 64;
+const CURRENT_INSTRUCTION: u16 = u16::MAX; // Offsets refer to the ed25519 instruction itself
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Attestation {
+    pub buyer: Pubkey,
+    pub expiry: i64,
+    pub jurisdiction: [u8; 2],
+}
+
+// When KYC is required, the instruction right before the buy must be an ed25519
+// program instruction verifying one signature by the KYC authority over an
+// attestation for `buyer` that hasn't expired and isn't from a blocked jurisdiction.
+// The ed25519 program has already checked the signature by the time we run.
+pub fn verify_attestation(instructions_sysvar: &AccountInfo, kyc: &KycConfig, buyer: &Pubkey) -> Result<()> {
+    if !kyc.required {
+        return Ok(());
+    }
+
+    let current_index = load_current_index_checked(instructions_sysvar)?;
+    require!(current_index > 0, ErrorCode::KycRequired);
+    let ed25519_ix = load_instruction_at_checked((current_index - 1) as usize, instructions_sysvar)?;
+    require_keys_eq!(ed25519_ix.program_id, ed25519_program::ID, ErrorCode::KycRequired);
+
+    let attestation = parse_ed25519_attestation(&ed25519_ix.data, &kyc.authority)?;
+    check_attestation(&attestation, kyc, buyer, Clock::get()?.unix_timestamp)?;
+
+    msg!("🪪 KYC attestation accepted (jurisdiction {}{})",
+         attestation.jurisdiction[0] as char, attestation.jurisdiction[1] as char);
+    Ok(())
+}
+
+// The attestation must be for `buyer`, unexpired at `now` and from an allowed jurisdiction
+fn check_attestation(attestation: &Attestation, kyc: &KycConfig, buyer: &Pubkey, now: i64) -> Result<()> {
+    require_keys_eq!(attestation.buyer, *buyer, ErrorCode::InvalidKycAttestation);
+    require!(attestation.expiry > now, ErrorCode::KycAttestationExpired);
+    require!(
+        !kyc.is_blocked(&attestation.jurisdiction),
+        ErrorCode::JurisdictionBlocked
+    );
+    Ok(())
+}
+
+// Extracts the attestation from ed25519 instruction data, requiring exactly one
+// signature by `authority` with all offsets pointing into this same instruction
+fn parse_ed25519_attestation(data: &[u8], authority: &Pubkey) -> Result<Attestation> {
+    require!(
+        data.len() >= SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN && data[0] == 1,
+        ErrorCode::InvalidKycAttestation
+    );
+
+    let offsets = &data[SIGNATURE_OFFSETS_START..SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN];
+    let read_u16 = |at: usize| u16::from_le_bytes([offsets[at], offsets[at + 1]]);
+    let signature_offset = read_u16(0) as usize;
+    let signature_ix = read_u16(2);
+    let pubkey_offset = read_u16(4) as usize;
+    let pubkey_ix = read_u16(6);
+    let message_offset = read_u16(8) as usize;
+    let message_size = read_u16(10) as usize;
+    let message_ix = read_u16(12);
+
+    require!(
+        signature_ix == CURRENT_INSTRUCTION
+            && pubkey_ix == CURRENT_INSTRUCTION
+            && message_ix == CURRENT_INSTRUCTION,
+        ErrorCode::InvalidKycAttestation
+    );
+    require!(
+        data.len() >= signature_offset + SIGNATURE_LEN,
+        ErrorCode::InvalidKycAttestation
+    );
+
+    let signer = data
+        .get(pubkey_offset..pubkey_offset + PUBKEY_LEN)
+        .ok_or(ErrorCode::InvalidKycAttestation)?;
+    require!(signer == authority.as_ref(), ErrorCode::InvalidKycAttestation);
+
+    require!(message_size == ATTESTATION_LEN, ErrorCode::InvalidKycAttestation);
+    let message = data
+        .get(message_offset..message_offset + ATTESTATION_LEN)
+        .ok_or(ErrorCode::InvalidKycAttestation)?;
+
+    let mut expiry = [0u8; 8];
+    expiry.copy_from_slice(&message[32..40]);
+    Ok(Attestation {
+        buyer: Pubkey::try_from(&message[..32]).map_err(|_| error!(ErrorCode::InvalidKycAttestation))?,
+        expiry: i64::from_le_bytes(expiry),
+        jurisdiction: [message[40], message[41]],
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::assert_error;
+    use crate::state::MAX_BLOCKED_JURISDICTIONS;
+
+    const NOW: i64 = 1_700_000_000;
+    const DATA_START: usize = SIGNATURE_OFFSETS_START + SIGNATURE_OFFSETS_LEN;
+
+    fn attestation_message(buyer: &Pubkey, expiry: i64, jurisdiction: &[u8; 2]) -> Vec<u8> {
+        [buyer.as_ref(), &expiry.to_le_bytes(), jurisdiction].concat()
+    }
+
+    // Ed25519 instruction data for one signature with all data after the offsets block:
+    // pubkey, then signature, then message. The signature itself is checked by the
+    // ed25519 program, so zeroes are enough here.
+    fn ed25519_data(signer: &Pubkey, message: &[u8], instruction_index: u16) -> Vec<u8> {
+        let pubkey_offset = DATA_START as u16;
+        let signature_offset = pubkey_offset + PUBKEY_LEN as u16;
+        let message_offset = signature_offset + SIGNATURE_LEN as u16;
+
+        let mut data = vec![1u8, 0];
+        for value in [
+            signature_offset,
+            instruction_index,
+            pubkey_offset,
+            instruction_index,
+            message_offset,
+            message.len() as u16,
+            instruction_index,
+        ] {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+        data.extend_from_slice(signer.as_ref());
+        data.extend_from_slice(&[0u8; SIGNATURE_LEN]);
+        data.extend_from_slice(message);
+        data
+    }
+
+    fn kyc_config(authority: Pubkey, blocked: &[[u8; 2]]) -> KycConfig {
+        let mut blocked_jurisdictions = [[0u8; 2]; MAX_BLOCKED_JURISDICTIONS];
+        blocked_jurisdictions[..blocked.len()].copy_from_slice(blocked);
+        KycConfig {
+            required: true,
+            authority,
+            blocked_jurisdictions,
+            blocked_count: blocked.len() as u8,
+        }
+    }
+
+    #[test]
+    fn valid_attestation_is_accepted() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let kyc = kyc_config(authority, &[*b"KP"]);
+        let data = ed25519_data(&authority, &attestation_message(&buyer, NOW + 60, b"DE"), CURRENT_INSTRUCTION);
+
+        let attestation = parse_ed25519_attestation(&data, &authority).unwrap();
+        assert_eq!(attestation, Attestation { buyer, expiry: NOW + 60, jurisdiction: *b"DE" });
+        assert!(check_attestation(&attestation, &kyc, &buyer, NOW).is_ok());
+    }
+
+    #[test]
+    fn wrong_signer_is_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let impostor = Pubkey::new_unique();
+        let data = ed25519_data(&impostor, &attestation_message(&buyer, NOW + 60, b"DE"), CURRENT_INSTRUCTION);
+
+        assert_error(parse_ed25519_attestation(&data, &authority), ErrorCode::InvalidKycAttestation);
+    }
+
+    #[test]
+    fn wrong_buyer_is_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let kyc = kyc_config(authority, &[]);
+        let data = ed25519_data(&authority, &attestation_message(&buyer, NOW + 60, b"DE"), CURRENT_INSTRUCTION);
+
+        let attestation = parse_ed25519_attestation(&data, &authority).unwrap();
+        assert_error(check_attestation(&attestation, &kyc, &Pubkey::new_unique(), NOW), ErrorCode::InvalidKycAttestation);
+    }
+
+    #[test]
+    fn expired_attestation_is_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let kyc = kyc_config(authority, &[]);
+
+        for expiry in [NOW, NOW - 1] {
+            let data = ed25519_data(&authority, &attestation_message(&buyer, expiry, b"DE"), CURRENT_INSTRUCTION);
+            let attestation = parse_ed25519_attestation(&data, &authority).unwrap();
+            assert_error(check_attestation(&attestation, &kyc, &buyer, NOW), ErrorCode::KycAttestationExpired);
+        }
+    }
+
+    #[test]
+    fn blocked_jurisdictions_are_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let kyc = kyc_config(authority, &[*b"KP", *b"IR"]);
+
+        for jurisdiction in [b"KP", b"IR"] {
+            let data = ed25519_data(&authority, &attestation_message(&buyer, NOW + 60, jurisdiction), CURRENT_INSTRUCTION);
+            let attestation = parse_ed25519_attestation(&data, &authority).unwrap();
+            assert_error(check_attestation(&attestation, &kyc, &buyer, NOW), ErrorCode::JurisdictionBlocked);
+        }
+
+        // Unused slots past blocked_count don't block anything
+        let data = ed25519_data(&authority, &attestation_message(&buyer, NOW + 60, &[0, 0]), CURRENT_INSTRUCTION);
+        let attestation = parse_ed25519_attestation(&data, &authority).unwrap();
+        assert!(check_attestation(&attestation, &kyc, &buyer, NOW).is_ok());
+    }
+
+    #[test]
+    fn multiple_signatures_are_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let mut data = ed25519_data(&authority, &attestation_message(&buyer, NOW + 60, b"DE"), CURRENT_INSTRUCTION);
+        data[0] = 2;
+
+        assert_error(parse_ed25519_attestation(&data, &authority), ErrorCode::InvalidKycAttestation);
+    }
+
+    #[test]
+    fn offsets_into_another_instruction_are_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let message = attestation_message(&buyer, NOW + 60, b"DE");
+
+        // All offsets pointing at another instruction
+        let data = ed25519_data(&authority, &message, 0);
+        assert_error(parse_ed25519_attestation(&data, &authority), ErrorCode::InvalidKycAttestation);
+
+        // Only one of signature, pubkey or message pointing elsewhere
+        for field in [2, 6, 12] {
+            let mut data = ed25519_data(&authority, &message, CURRENT_INSTRUCTION);
+            let at = SIGNATURE_OFFSETS_START + field;
+            data[at..at + 2].copy_from_slice(&1u16.to_le_bytes());
+            assert_error(parse_ed25519_attestation(&data, &authority), ErrorCode::InvalidKycAttestation);
+        }
+    }
+
+    #[test]
+    fn truncated_data_is_rejected() {
+        let (authority, buyer) = (Pubkey::new_unique(), Pubkey::new_unique());
+        let data = ed25519_data(&authority, &attestation_message(&buyer, NOW + 60, b"DE"), CURRENT_INSTRUCTION);
+
+        for len in [0, DATA_START - 1, DATA_START + PUBKEY_LEN, data.len() - 1] {
+            assert_error(parse_ed25519_attestation(&data[..len], &authority), ErrorCode::InvalidKycAttestation);
+        }
+    }
+}
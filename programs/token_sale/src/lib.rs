@@ -1,10 +1,17 @@
 pub mod instructions;
 pub mod state;
 pub mod error;
+pub mod oracle;
+pub mod curve;
+pub mod kyc;
+pub mod token_transfer;
 
 use anchor_lang::prelude::*;
 use instructions::*;
-use state::config::{RoundInfo, AllRoundsInfo}; // Add this import
+use state::config::{RoundInfo, AllRoundsInfo, OracleKind, ReferralRewardKind}; // Add this import
+use state::round::RoundParams;
+use state::revenue::Recipient;
+use state::payment::PriceSource;
 
 declare_id!("Bv8sK4AN9bTrcLfH8zvCs8yiScbQZtT8nnss5uEticDe");
 
@@ -25,33 +32,176 @@ pub mod simple_token_sale {
         instructions::admin::update_mint(ctx, new_mint)
     }
 
+    pub fn set_oracle_feeds(
+        ctx: Context<OnlyOwner>,
+        eur_usd_feed: Pubkey,
+        sol_usd_feed: Pubkey,
+    ) -> Result<()> {
+        instructions::admin::set_oracle_feeds(ctx, eur_usd_feed, sol_usd_feed)
+    }
+
+    pub fn set_pull_oracle_feeds(
+        ctx: Context<OnlyOwner>,
+        eur_usd_feed_id: [u8; 32],
+        sol_usd_feed_id: [u8; 32],
+    ) -> Result<()> {
+        instructions::admin::set_pull_oracle_feeds(ctx, eur_usd_feed_id, sol_usd_feed_id)
+    }
+
+    pub fn set_oracle_kind(ctx: Context<OnlyOwner>, kind: OracleKind) -> Result<()> {
+        instructions::admin::set_oracle_kind(ctx, kind)
+    }
+
+    pub fn set_oracle_limits(
+        ctx: Context<OnlyOwner>,
+        max_price_age: u64,
+        max_confidence_bps: u16,
+    ) -> Result<()> {
+        instructions::admin::set_oracle_limits(ctx, max_price_age, max_confidence_bps)
+    }
+
+    pub fn set_kyc_config(ctx: Context<OnlyOwner>, required: bool, authority: Pubkey) -> Result<()> {
+        instructions::admin::set_kyc_config(ctx, required, authority)
+    }
+
+    pub fn set_blocked_jurisdictions(ctx: Context<OnlyOwner>, jurisdictions: Vec<[u8; 2]>) -> Result<()> {
+        instructions::admin::set_blocked_jurisdictions(ctx, jurisdictions)
+    }
+
     // Sale functions
-    pub fn buy(ctx: Context<Buy>, amount: u64) -> Result<()> {
-        instructions::sale::buy(ctx, amount)
+    pub fn buy(
+        ctx: Context<Buy>,
+        amount: u64,
+        max_total_payment: u64,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
+        instructions::sale::buy(ctx, amount, max_total_payment, allowlist)
+    }
+
+    pub fn buy_with_token<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyWithToken<'info>>,
+        amount: u64,
+        max_total_payment: u64,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
+        instructions::sale::buy_with_token(ctx, amount, max_total_payment, allowlist)
+    }
+
+    // Exact-input variants: spend up to `payment`, receive the largest affordable amount
+    pub fn buy_exact_in(
+        ctx: Context<Buy>,
+        payment: u64,
+        min_tokens_out: u64,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
+        instructions::sale::buy_exact_in(ctx, payment, min_tokens_out, allowlist)
+    }
+
+    pub fn buy_with_token_exact_in<'info>(
+        ctx: Context<'_, '_, 'info, 'info, BuyWithToken<'info>>,
+        payment: u64,
+        min_tokens_out: u64,
+        allowlist: Option<AllowlistProof>,
+    ) -> Result<()> {
+        instructions::sale::buy_with_token_exact_in(ctx, payment, min_tokens_out, allowlist)
+    }
+
+    // Payment currency registry
+    pub fn add_payment_currency(ctx: Context<AddPaymentCurrency>, price_source: PriceSource) -> Result<()> {
+        instructions::payment::add_payment_currency(ctx, price_source)
+    }
+
+    pub fn set_payment_currency(
+        ctx: Context<SetPaymentCurrency>,
+        mint: Pubkey,
+        enabled: bool,
+        price_source: PriceSource,
+    ) -> Result<()> {
+        instructions::payment::set_payment_currency(ctx, mint, enabled, price_source)
+    }
+
+    // Vesting functions
+    pub fn claim_vested(ctx: Context<ClaimVested>) -> Result<()> {
+        instructions::vesting::claim_vested(ctx)
     }
 
-    pub fn buy_with_eurc(ctx: Context<BuyWithEurc>, amount: u64) -> Result<()> {
-        instructions::sale::buy_with_eurc(ctx, amount)
+    // Escrow functions
+    pub fn initialize_vaults(ctx: Context<InitializeVaults>) -> Result<()> {
+        instructions::escrow::initialize_vaults(ctx)
     }
 
-    pub fn buy_with_usdc(ctx: Context<BuyWithUsdc>, amount: u64) -> Result<()> {
-        instructions::sale::buy_with_usdc(ctx, amount)
+    pub fn set_sale_caps(ctx: Context<SetSaleCaps>, escrow: bool, soft_cap: u64, hard_cap: u64) -> Result<()> {
+        instructions::escrow::set_sale_caps(ctx, escrow, soft_cap, hard_cap)
+    }
+
+    pub fn refund(ctx: Context<Refund>) -> Result<()> {
+        instructions::escrow::refund(ctx)
+    }
+
+    pub fn refund_token<'info>(ctx: Context<'_, '_, 'info, 'info, RefundToken<'info>>) -> Result<()> {
+        instructions::escrow::refund_token(ctx)
+    }
+
+    pub fn finalize(ctx: Context<Finalize>) -> Result<()> {
+        instructions::escrow::finalize(ctx)
+    }
+
+    pub fn withdraw<'info>(ctx: Context<'_, '_, 'info, 'info, Withdraw<'info>>, sol_amount: u64) -> Result<()> {
+        instructions::escrow::withdraw(ctx, sol_amount)
+    }
+
+    pub fn withdraw_token<'info>(ctx: Context<'_, '_, 'info, 'info, WithdrawToken<'info>>, amount: u64) -> Result<()> {
+        instructions::escrow::withdraw_token(ctx, amount)
+    }
+
+    pub fn set_revenue_split(ctx: Context<SetRevenueSplit>, recipients: Vec<Recipient>) -> Result<()> {
+        instructions::revenue::set_revenue_split(ctx, recipients)
+    }
+
+    // Referral functions
+    pub fn set_referral_config(
+        ctx: Context<SetReferralConfig>,
+        enabled: bool,
+        reward_bps: u16,
+        reward_kind: ReferralRewardKind,
+    ) -> Result<()> {
+        instructions::admin::set_referral_config(ctx, enabled, reward_bps, reward_kind)
+    }
+
+    pub fn register_referrer(ctx: Context<RegisterReferrer>) -> Result<()> {
+        instructions::referral::register_referrer(ctx)
     }
 
     // Airdrop functions
+    #[allow(clippy::too_many_arguments)]
     pub fn set_merkle_root(
         ctx: Context<SetMerkleRoot>, 
+        campaign_id: u64,
         root: [u8; 32],
+        leaf_version: u8,
         airdrop_amount: u64,
+        total_token_budget: u64,
         max_claims: u64,
         start_time: i64,
         end_time: i64,
     ) -> Result<()> {
-        instructions::airdrop::set_merkle_root_handler(ctx, root, airdrop_amount, max_claims, start_time, end_time)
+        instructions::airdrop::set_merkle_root_handler(
+            ctx, campaign_id, root, leaf_version, airdrop_amount, total_token_budget, max_claims, start_time, end_time,
+        )
+    }
+
+    pub fn init_claim_bitmap(ctx: Context<InitClaimBitmap>, campaign_id: u64, chunk_index: u32) -> Result<()> {
+        instructions::airdrop::init_claim_bitmap_handler(ctx, campaign_id, chunk_index)
     }
 
-    pub fn claim(ctx: Context<Claim>, amount: u64, proof: Vec<[u8; 32]>) -> Result<()> {
-        instructions::airdrop::claim_handler(ctx, amount, proof)
+    pub fn claim(
+        ctx: Context<Claim>,
+        campaign_id: u64,
+        index: u64,
+        amount: u64,
+        proof: Vec<[u8; 32]>,
+    ) -> Result<()> {
+        instructions::airdrop::claim_handler(ctx, campaign_id, index, amount, proof)
     }
 
     // Info functions
@@ -62,7 +212,37 @@ pub mod simple_token_sale {
     pub fn get_all_rounds_info(ctx: Context<GetRoundInfo>) -> Result<AllRoundsInfo> {
         instructions::sale::get_all_rounds_info(ctx)
     }
-    pub fn update_sale_start_time(ctx: Context<OnlyOwner>) -> Result<()> {
-        instructions::admin::update_sale_start_time(ctx)
+
+    // Pricing functions
+    pub fn update_pricing_params(
+        ctx: Context<UpdatePricingParams>,
+        price_max: u64,
+        price_min: u64,
+        midpoint: u64,
+        steepness: u64,
+    ) -> Result<()> {
+        instructions::pricing::update_pricing_params(ctx, price_max, price_min, midpoint, steepness)
+    }
+
+    // Round schedule functions
+    pub fn add_round(ctx: Context<AddRound>, params: RoundParams) -> Result<()> {
+        instructions::rounds::add_round(ctx, params)
+    }
+
+    pub fn edit_round(ctx: Context<ManageRounds>, round_index: u8, params: RoundParams) -> Result<()> {
+        instructions::rounds::edit_round(ctx, round_index, params)
+    }
+
+    pub fn close_round(ctx: Context<ManageRounds>, round_index: u8) -> Result<()> {
+        instructions::rounds::close_round(ctx, round_index)
+    }
+
+    pub fn set_round_allowlist(
+        ctx: Context<ManageRounds>,
+        round_index: u8,
+        allowlist_root: [u8; 32],
+        leaf_version: u8,
+    ) -> Result<()> {
+        instructions::rounds::set_round_allowlist(ctx, round_index, allowlist_root, leaf_version)
     }
 }
\ No newline at end of file
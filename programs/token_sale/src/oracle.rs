@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+use pyth_sdk_solana::state::SolanaPriceAccount;
+use pyth_solana_receiver_sdk::price_update::{PriceUpdateV2, VerificationLevel};
+use crate::state::{OracleConfig, PRICE_DECIMALS};
+use crate::error::ErrorCode;
+
+// Mock price data - using realistic current prices
+pub const MOCK_EUR_USD_RATE: u64 = 1_080_000; // 1.08 EUR/USD (6 decimals)
+pub const MOCK_SOL_USD_RATE: u64 = 140_000_000; // 140.00 SOL/USD (6 decimals)
+pub const MOCK_USDC_USD_RATE: u64 = 1_000_000; // 1.00 USDC/USD (6 decimals)
+
+// Price pairs the sale needs
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Feed {
+    EurUsd,
+    SolUsd,
+    Currency { account: Pubkey, feed_id: [u8; 32] }, // A registered payment currency's USD feed
+}
+
+impl OracleConfig {
+    pub fn feed_account(&self, feed: Feed) -> Pubkey {
+        match feed {
+            Feed::EurUsd => self.eur_usd_feed,
+            Feed::SolUsd => self.sol_usd_feed,
+            Feed::Currency { account, .. } => account,
+        }
+    }
+
+    pub fn feed_id(&self, feed: Feed) -> [u8; 32] {
+        match feed {
+            Feed::EurUsd => self.eur_usd_feed_id,
+            Feed::SolUsd => self.sol_usd_feed_id,
+            Feed::Currency { feed_id, .. } => feed_id,
+        }
+    }
+}
+
+// Oracle price entry point - reads Pyth unless built with the `mock-oracle` feature
+#[cfg(not(feature = "mock-oracle"))]
+pub fn get_price(price_account: &AccountInfo, oracle: &OracleConfig, feed: Feed) -> Result<u64> {
+    use crate::state::OracleKind;
+
+    match oracle.kind {
+        OracleKind::Legacy => {
+            require_keys_eq!(
+                price_account.key(),
+                oracle.feed_account(feed),
+                ErrorCode::InvalidPythAccount
+            );
+            get_validated_price(price_account, oracle.max_price_age, oracle.max_confidence_bps)
+        }
+        OracleKind::Pull => get_validated_pull_price(
+            price_account,
+            &oracle.feed_id(feed),
+            oracle.max_price_age,
+            oracle.max_confidence_bps,
+        ),
+    }
+}
+
+#[cfg(feature = "mock-oracle")]
+pub fn get_price(_price_account: &AccountInfo, _oracle: &OracleConfig, feed: Feed) -> Result<u64> {
+    get_mock_price(feed)
+}
+
+// Mock price function - returns hardcoded rates for each feed
+pub fn get_mock_price(feed: Feed) -> Result<u64> {
+    msg!("🧪 Using mock price data");
+
+    match feed {
+        Feed::EurUsd => {
+            msg!("   EUR/USD rate: {}", MOCK_EUR_USD_RATE);
+            Ok(MOCK_EUR_USD_RATE)
+        }
+        Feed::SolUsd => {
+            msg!("   SOL/USD rate: {}", MOCK_SOL_USD_RATE);
+            Ok(MOCK_SOL_USD_RATE)
+        }
+        Feed::Currency { .. } => {
+            msg!("   Currency/USD rate: {}", MOCK_USDC_USD_RATE);
+            Ok(MOCK_USDC_USD_RATE)
+        }
+    }
+}
+
+// Reads a legacy Pyth price account and returns the price normalized to 6 decimals.
+// Rejects prices older than `max_price_age` seconds and prices whose confidence
+// interval is wider than `max_confidence_bps` of the price.
+pub fn get_validated_price(
+    price_account: &AccountInfo,
+    max_price_age: u64,
+    max_confidence_bps: u16,
+) -> Result<u64> {
+    let price_feed = SolanaPriceAccount::account_info_to_feed(price_account)
+        .map_err(|_| error!(ErrorCode::InvalidPythAccount))?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let price = price_feed
+        .get_price_no_older_than(current_time, max_price_age)
+        .ok_or(error!(ErrorCode::StalePrice))?;
+
+    msg!("📈 Pyth price: {} x 10^{} (conf: {}, published: {})",
+         price.price, price.expo, price.conf, price.publish_time);
+
+    normalize_price(price.price, price.conf, price.expo, max_confidence_bps)
+}
+
+// Reads a Pyth receiver PriceUpdateV2 account for `feed_id` and returns the price
+// normalized to 6 decimals. The update must be fully verified (all Wormhole
+// guardian signatures checked) and published within `max_price_age` seconds.
+pub fn get_validated_pull_price(
+    price_update_account: &AccountInfo,
+    feed_id: &[u8; 32],
+    max_price_age: u64,
+    max_confidence_bps: u16,
+) -> Result<u64> {
+    // Must be owned by the Pyth receiver program and carry the PriceUpdateV2 discriminator
+    require_keys_eq!(
+        *price_update_account.owner,
+        PriceUpdateV2::owner(),
+        ErrorCode::InvalidPythAccount
+    );
+    let data = price_update_account.try_borrow_data()?;
+    let price_update = PriceUpdateV2::try_deserialize(&mut &data[..])
+        .map_err(|_| error!(ErrorCode::InvalidPythAccount))?;
+
+    require!(
+        price_update.verification_level.gte(VerificationLevel::Full),
+        ErrorCode::InsufficientVerification
+    );
+
+    let price = price_update
+        .get_price_unchecked(feed_id)
+        .map_err(|_| error!(ErrorCode::InvalidPythAccount))?;
+
+    let current_time = Clock::get()?.unix_timestamp;
+    let age = current_time.saturating_sub(price.publish_time);
+    require!(age <= max_price_age as i64, ErrorCode::StalePrice);
+
+    msg!("📈 Pyth pull price: {} x 10^{} (conf: {}, published: {})",
+         price.price, price.exponent, price.conf, price.publish_time);
+
+    normalize_price(price.price, price.conf, price.exponent, max_confidence_bps)
+}
+
+// Confidence check shared by both backends, then rescale to PRICE_DECIMALS
+fn normalize_price(price: i64, conf: u64, expo: i32, max_confidence_bps: u16) -> Result<u64> {
+    require!(price > 0, ErrorCode::InvalidPrice);
+    let price_value = price as u64;
+
+    // conf / price <= max_confidence_bps / 10_000
+    let conf_scaled = (conf as u128)
+        .checked_mul(10_000)
+        .ok_or(ErrorCode::Overflow)?;
+    let max_conf_scaled = (price_value as u128)
+        .checked_mul(max_confidence_bps as u128)
+        .ok_or(ErrorCode::Overflow)?;
+    require!(conf_scaled <= max_conf_scaled, ErrorCode::PriceTooVolatile);
+
+    scale_price(price_value, expo, PRICE_DECIMALS)
+}
+
+// Rescale a Pyth price from 10^expo to `target_decimals` decimals
+pub fn scale_price(price: u64, expo: i32, target_decimals: i32) -> Result<u64> {
+    let shift = expo.checked_add(target_decimals).ok_or(ErrorCode::Overflow)?;
+    let factor = 10u64
+        .checked_pow(shift.unsigned_abs())
+        .ok_or(ErrorCode::Overflow)?;
+
+    let scaled = if shift >= 0 {
+        price.checked_mul(factor).ok_or(ErrorCode::Overflow)?
+    } else {
+        price / factor
+    };
+
+    require!(scaled > 0, ErrorCode::InvalidPrice);
+    Ok(scaled)
+}
@@ -0,0 +1,61 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::state::round::MAX_ROUNDS;
+use crate::state::payment::MAX_PAYMENT_CURRENCIES;
+
+// Currency a purchase was paid in
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum PaymentCurrency {
+    Sol,
+    Token(u8), // Index into PaymentRegistry::currencies
+}
+
+// Per-buyer purchase ledger, created on the buyer's first purchase
+#[account]
+#[derive(InitSpace)]
+pub struct BuyerRecord {
+    pub buyer: Pubkey,
+    pub tokens_per_round: [u64; MAX_ROUNDS], // Base units, indexed like RoundSchedule::rounds
+    pub total_tokens: u64,
+    pub total_paid_sol: u64, // Lamports
+    pub paid_per_currency: [u64; MAX_PAYMENT_CURRENCIES], // Indexed like PaymentRegistry::currencies
+    pub first_purchase_time: i64,
+    pub last_purchase_time: i64,
+    pub purchase_count: u32,
+    pub referrer: Pubkey, // Fixed at the first purchase (default if none)
+    pub bump: u8,
+}
+
+impl BuyerRecord {
+    pub fn record_purchase(
+        &mut self,
+        round_index: usize,
+        amount: u64,
+        currency: PaymentCurrency,
+        paid: u64,
+        now: i64,
+    ) -> Result<()> {
+        let round_tokens = self
+            .tokens_per_round
+            .get_mut(round_index)
+            .ok_or(ErrorCode::RoundNotFound)?;
+        *round_tokens = round_tokens.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        self.total_tokens = self.total_tokens.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+
+        let total_paid = match currency {
+            PaymentCurrency::Sol => &mut self.total_paid_sol,
+            PaymentCurrency::Token(index) => self
+                .paid_per_currency
+                .get_mut(index as usize)
+                .ok_or(ErrorCode::CurrencyNotAccepted)?,
+        };
+        *total_paid = total_paid.checked_add(paid).ok_or(ErrorCode::Overflow)?;
+
+        if self.purchase_count == 0 {
+            self.first_purchase_time = now;
+        }
+        self.last_purchase_time = now;
+        self.purchase_count = self.purchase_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
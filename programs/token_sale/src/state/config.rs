@@ -1,34 +1,12 @@
 use anchor_lang::prelude::*;
 
-// Round configuration
-#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
-pub enum Round {
-    First = 1,  // 10% cheaper than third round
-    Second = 2, // 5% cheaper than third round  
-    Third = 3,  // Base prices
-}
-
-impl Round {
-    pub fn get_discount_multiplier(&self) -> f64 {
-        match self {
-            Round::First => 0.90,  // 10% cheaper = 90% of base price
-            Round::Second => 0.95, // 5% cheaper = 95% of base price
-            Round::Third => 1.00,  // Base price = 100%
-        }
-    }
-}
+// Oracle price validation defaults (owner can change them with set_oracle_limits)
+pub const DEFAULT_MAX_PRICE_AGE: u64 = 60;        // Reject prices older than 60 seconds
+pub const DEFAULT_MAX_CONFIDENCE_BPS: u16 = 100;  // Reject prices with confidence wider than 1%
+pub const PRICE_DECIMALS: i32 = 6;                // All oracle prices are normalized to 6 decimals
 
-// Sigmoid pricing parameters (in lamports - 6 decimals)
-pub const BASE_PRICE_MAX: u64 = 290_000;    // 0.290 EURC per token (max price for very small purchases)
-pub const BASE_PRICE_MIN: u64 = 180_000;    // 0.180 EURC per token (min price for very large purchases)
-pub const SIGMOID_MIDPOINT: f64 = 10_000.0; // 10K tokens = midpoint of discount curve
-pub const SIGMOID_STEEPNESS: f64 = 0.0003;  // Controls how quickly discount applies
-
-pub const TOKENS_PER_ROUND: u64 = 1_000_000; // 1 million tokens per round
-
-// USDC and EURC mint addresses (devnet)
-pub const USDC_MINT: &str = "4zMMC9srt5Ri5X14GAgXhaHii3GnPAEERYPJgZJDncDU";
-pub const EURC_MINT: &str = "HzwqbKZw8HxMN6bF2yFZNrht3c2iXXzpKcFu7uBEDKtr";
+pub const MAX_BLOCKED_JURISDICTIONS: usize = 16;
+pub const MAX_REFERRAL_REWARD_BPS: u16 = 5_000; // Referral rewards can't exceed half a purchase
 
 #[account]
 pub struct Config {
@@ -38,40 +16,194 @@ pub struct Config {
     pub mint: Pubkey,
     pub paused: bool,
     pub bump: u8,
-    pub sale_start_time: i64,
-    pub round1_tokens_sold: u64,
-    pub round2_tokens_sold: u64,
-    pub round3_tokens_sold: u64,
+    pub oracle: OracleConfig,
+    pub kyc: KycConfig,
+    pub caps: SaleCaps,
+    pub referral: ReferralConfig,
 }
 
 impl Config {
-    pub const SIZE: usize = 1 + 32 + 32 + 32 + 1 + 1 + 8 + 8 + 8 + 8;
+    pub const SIZE: usize = 1 + 32 + 32 + 32 + 1 + 1
+        + OracleConfig::SIZE + KycConfig::SIZE + SaleCaps::SIZE + ReferralConfig::SIZE;
+}
+
+// Sale-wide caps in base units of the sale mint. Payments always land in the program
+// vault. With `escrow` on they stay locked there: below `soft_cap` at sale end buyers
+// can refund, otherwise the owner finalizes and can then withdraw to the treasury.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct SaleCaps {
+    pub escrow: bool,
+    pub soft_cap: u64,
+    pub hard_cap: u64, // 0 = only the round caps apply
+    pub finalized: bool,
+}
+
+impl SaleCaps {
+    pub const SIZE: usize = 1 + 8 + 8 + 1;
+
+    // Tokens that can still be sold before the hard cap
+    pub fn remaining(&self, total_sold: u64) -> u64 {
+        if self.hard_cap == 0 {
+            return u64::MAX;
+        }
+        self.hard_cap.saturating_sub(total_sold)
+    }
+
+    pub fn soft_cap_reached(&self, total_sold: u64) -> bool {
+        total_sold >= self.soft_cap
+    }
+}
+
+// Which Pyth backend buy instructions read prices from
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum OracleKind {
+    #[default]
+    Legacy, // Push price accounts (pyth_sdk_solana), identified by account address
+    Pull,   // PriceUpdateV2 accounts (Pyth receiver), identified by feed ID
+}
+
+// Oracle section of Config - feed accounts and price validation limits
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct OracleConfig {
+    pub kind: OracleKind,
+    pub eur_usd_feed: Pubkey,
+    pub sol_usd_feed: Pubkey,
+    pub eur_usd_feed_id: [u8; 32],
+    pub sol_usd_feed_id: [u8; 32],
+    pub max_price_age: u64,
+    pub max_confidence_bps: u16,
+}
+
+impl OracleConfig {
+    pub const SIZE: usize = 1 + 32 + 32 + 32 + 32 + 8 + 2;
+}
+
+// KYC section of Config - when required, buys need an ed25519 attestation from `authority`
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct KycConfig {
+    pub required: bool,
+    pub authority: Pubkey,
+    pub blocked_jurisdictions: [[u8; 2]; MAX_BLOCKED_JURISDICTIONS], // ISO 3166-1 alpha-2 codes
+    pub blocked_count: u8,
+}
+
+impl KycConfig {
+    pub const SIZE: usize = 1 + 32 + 2 * MAX_BLOCKED_JURISDICTIONS + 1;
+
+    pub fn is_blocked(&self, jurisdiction: &[u8; 2]) -> bool {
+        self.blocked_jurisdictions[..self.blocked_count as usize].contains(jurisdiction)
+    }
+}
+
+// How referrers are rewarded
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub enum ReferralRewardKind {
+    #[default]
+    BonusTokens,  // reward_bps of the tokens bought, minted to the referrer
+    PaymentShare, // reward_bps of the payment, sent to the referrer instead of the vault
+}
+
+// Referral section of Config
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, Default, PartialEq)]
+pub struct ReferralConfig {
+    pub enabled: bool,
+    pub reward_bps: u16,
+    pub reward_kind: ReferralRewardKind,
+}
+
+impl ReferralConfig {
+    pub const SIZE: usize = 1 + 2 + 1;
+
+    // (bonus tokens, payment share) for a purchase of `amount` base units costing `total`
+    pub fn rewards(&self, amount: u64, total: u64) -> (u64, u64) {
+        let share = |value: u64| ((value as u128 * self.reward_bps as u128) / 10_000) as u64;
+        match self.reward_kind {
+            ReferralRewardKind::BonusTokens => (share(amount), 0),
+            ReferralRewardKind::PaymentShare => (0, share(total)),
+        }
+    }
+
+    // Largest purchase whose bonus still fits, so purchase plus bonus stays within `supply`
+    pub fn max_purchase_within(&self, supply: u64) -> u64 {
+        match self.reward_kind {
+            ReferralRewardKind::BonusTokens => {
+                (supply as u128 * 10_000 / (10_000 + self.reward_bps as u128)) as u64
+            }
+            ReferralRewardKind::PaymentShare => supply,
+        }
+    }
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct RoundInfo {
-    pub current_round: u8,
+    pub current_round: u8, // 1-based round number
     pub round_start_time: i64,
     pub round_end_time: i64,
     pub sale_start_time: i64,
     pub total_duration: i64,
-    pub round_tokens_sold: u64,
+    pub round_tokens_sold: u64, // Token amounts are in base units of the sale mint
     pub remaining_tokens: u64,
-    pub tokens_per_round: u64,
+    pub round_token_cap: u64,
+    pub discount_bps: u16,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub min_purchase: u64,
+    pub max_per_wallet: u64,
+    pub allowlist_root: [u8; 32],
+    pub allowlist_leaf_version: u8,
+    pub total_rounds: u8,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct AllRoundsInfo {
-    pub round1: RoundDetails,
-    pub round2: RoundDetails,
-    pub round3: RoundDetails,
+    pub rounds: Vec<RoundDetails>,
     pub total_tokens_sold: u64,
     pub total_tokens: u64,
 }
 
 #[derive(AnchorSerialize, AnchorDeserialize, Clone, Debug)]
 pub struct RoundDetails {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub discount_bps: u16,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub min_purchase: u64,
+    pub max_per_wallet: u64,
+    pub allowlist_root: [u8; 32],
+    pub allowlist_leaf_version: u8,
     pub tokens_sold: u64,
     pub remaining: u64,
     pub total: u64,
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn remaining_under_hard_cap() {
+        let caps = SaleCaps { hard_cap: 1_000, ..SaleCaps::default() };
+        assert_eq!(caps.remaining(0), 1_000);
+        assert_eq!(caps.remaining(999), 1);
+        assert_eq!(caps.remaining(1_000), 0);
+        assert_eq!(caps.remaining(5_000), 0);
+    }
+
+    #[test]
+    fn zero_hard_cap_is_unlimited() {
+        let caps = SaleCaps::default();
+        assert_eq!(caps.remaining(0), u64::MAX);
+        assert_eq!(caps.remaining(u64::MAX), u64::MAX);
+    }
+
+    #[test]
+    fn soft_cap_reached_at_the_cap() {
+        let caps = SaleCaps { escrow: true, soft_cap: 1_000, ..SaleCaps::default() };
+        assert!(!caps.soft_cap_reached(999));
+        assert!(caps.soft_cap_reached(1_000));
+        assert!(caps.soft_cap_reached(1_001));
+        // No soft cap: reached from the start
+        assert!(SaleCaps::default().soft_cap_reached(0));
+    }
+}
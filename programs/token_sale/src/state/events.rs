@@ -3,7 +3,7 @@ use anchor_lang::prelude::*;
 #[event]
 pub struct TokenPurchasedWithSol {
     pub buyer: Pubkey,
-    pub token_amount: u64,
+    pub token_amount: u64, // Base units of the sale mint
     pub sol_amount: u64,
     pub sol_per_token: u64,
     pub eur_usd_rate: u64,
@@ -13,30 +13,88 @@ pub struct TokenPurchasedWithSol {
     pub payment_method: String,
     pub tier: String,
     pub round: u8,
+    pub referrer: Option<Pubkey>,
+    pub referral_reward: u64, // Bonus tokens or payment share, per the referral config
 }
 
 #[event]
-pub struct TokenPurchasedWithEurc {
+pub struct TokenPurchasedWithToken {
     pub buyer: Pubkey,
-    pub token_amount: u64,
-    pub eurc_amount: u64,
-    pub eurc_per_token: u64,
+    pub token_amount: u64, // Base units of the sale mint
+    pub payment_mint: Pubkey,
+    pub payment_amount: u64, // Base units of the payment mint sent by the buyer, fees included
+    pub transfer_fee: u64,   // Token-2022 transfer fee withheld on the vault's part
+    pub price_per_token: u64,
+    pub eur_usd_rate: u64,      // 6 decimals; EUR-priced currencies report 1.00 for both rates
+    pub currency_usd_rate: u64, // 6 decimals; 1.00 for USD-pegged currencies
+    pub equivalent_eurc_price: u64,
     pub timestamp: i64,
-    pub payment_method: String,
     pub tier: String,
     pub round: u8,
+    pub referrer: Option<Pubkey>,
+    pub referral_reward: u64, // Bonus tokens or payment share, per the referral config
 }
 
 #[event]
-pub struct TokenPurchasedWithUsdc {
+pub struct PricingCurveUpdated {
+    pub updated_by: Pubkey,
+    pub old_price_max: u64,
+    pub old_price_min: u64,
+    pub old_midpoint: u64,
+    pub old_steepness: u64,
+    pub new_price_max: u64,
+    pub new_price_min: u64,
+    pub new_midpoint: u64,
+    pub new_steepness: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct VestedTokensClaimed {
     pub buyer: Pubkey,
-    pub token_amount: u64,
-    pub usdc_amount: u64,
-    pub usdc_per_token: u64,
-    pub eur_usd_rate: u64,
-    pub equivalent_eurc_price: u64,
+    pub amount: u64, // Base units of the sale mint
+    pub remaining: u64,
     pub timestamp: i64,
-    pub payment_method: String,
-    pub tier: String,
-    pub round: u8,
-}
\ No newline at end of file
+}
+
+#[event]
+pub struct Refunded {
+    pub buyer: Pubkey,
+    pub tokens_burned: u64,
+    pub tokens_unvested: u64, // Vesting balance cancelled without minting
+    pub sol_amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct TokenRefunded {
+    pub buyer: Pubkey,
+    pub payment_mint: Pubkey,
+    pub amount: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct SaleFinalized {
+    pub total_tokens_sold: u64,
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct FundsWithdrawn {
+    pub withdrawn_by: Pubkey,
+    pub treasury: Pubkey,
+    pub split_recipients: u8, // 0 = paid to the treasury, otherwise see RevenueSharePaid
+    pub payment_mint: Option<Pubkey>, // None for SOL
+    pub amount: u64,                  // Lamports or base units of payment_mint
+    pub timestamp: i64,
+}
+
+#[event]
+pub struct RevenueSharePaid {
+    pub recipient: Pubkey,
+    pub bps: u16,
+    pub payment_mint: Option<Pubkey>, // None for SOL
+    pub amount: u64,
+    pub timestamp: i64,
+}
@@ -1,13 +1,28 @@
 pub mod config;
 pub mod events;
+pub mod round;
+pub mod pricing;
+pub mod vesting;
+pub mod buyer;
+pub mod revenue;
+pub mod referral;
+pub mod payment;
 
 // Re-export specific items instead of wildcard
 pub use config::{
-    Config, Round, RoundInfo, AllRoundsInfo, RoundDetails, 
-    BASE_PRICE_MAX, BASE_PRICE_MIN, SIGMOID_MIDPOINT,
-    SIGMOID_STEEPNESS, TOKENS_PER_ROUND,
-    USDC_MINT, EURC_MINT
+    Config, OracleConfig, OracleKind, KycConfig, SaleCaps, ReferralConfig, ReferralRewardKind, RoundInfo, AllRoundsInfo, RoundDetails, 
+    DEFAULT_MAX_PRICE_AGE, DEFAULT_MAX_CONFIDENCE_BPS, PRICE_DECIMALS, MAX_BLOCKED_JURISDICTIONS,
+    MAX_REFERRAL_REWARD_BPS,
 };
+pub use round::{Round, RoundParams, RoundSchedule, MAX_ROUNDS};
+pub use pricing::{PricingParams, STEEPNESS_SCALE};
+pub use vesting::{VestingAccount, VestingEntry};
+pub use buyer::{BuyerRecord, PaymentCurrency};
+pub use revenue::{Recipient, RevenueSplit, MAX_RECIPIENTS};
+pub use referral::ReferralRecord;
+pub use payment::{AcceptedCurrency, PaymentRegistry, PriceSource, MAX_PAYMENT_CURRENCIES};
 pub use events::{
-    TokenPurchasedWithSol, TokenPurchasedWithEurc, TokenPurchasedWithUsdc
+    TokenPurchasedWithSol, TokenPurchasedWithToken,
+    PricingCurveUpdated, VestedTokensClaimed, Refunded, TokenRefunded, SaleFinalized,
+    FundsWithdrawn, RevenueSharePaid
 };
\ No newline at end of file
@@ -0,0 +1,45 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+pub const MAX_PAYMENT_CURRENCIES: usize = 8;
+
+// How a currency is priced from the EURC-denominated sale price
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub enum PriceSource {
+    Eur,       // Euro stablecoin - charged the EURC price as is
+    UsdPegged, // Dollar stablecoin assumed at $1.00 - converted with EUR/USD
+    Oracle {
+        feed: Pubkey,      // Legacy CUR/USD price account
+        feed_id: [u8; 32], // CUR/USD pull feed ID
+    },
+}
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct AcceptedCurrency {
+    pub mint: Pubkey,
+    pub decimals: u8,
+    pub token_program: Pubkey, // Token or Token-2022
+    pub price_source: PriceSource,
+    pub enabled: bool,
+}
+
+// SPL currencies accepted by buy_with_token. Entries are only ever disabled, never
+// removed, so a currency's index stays valid in BuyerRecord and ReferralRecord.
+#[account]
+#[derive(InitSpace)]
+pub struct PaymentRegistry {
+    #[max_len(MAX_PAYMENT_CURRENCIES)]
+    pub currencies: Vec<AcceptedCurrency>,
+    pub bump: u8,
+}
+
+impl PaymentRegistry {
+    // Index and entry of `mint`, enabled or not
+    pub fn currency(&self, mint: &Pubkey) -> Result<(usize, AcceptedCurrency)> {
+        self.currencies
+            .iter()
+            .position(|currency| currency.mint == *mint)
+            .map(|index| (index, self.currencies[index]))
+            .ok_or(error!(ErrorCode::CurrencyNotAccepted))
+    }
+}
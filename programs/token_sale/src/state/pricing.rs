@@ -0,0 +1,36 @@
+use anchor_lang::prelude::*;
+use crate::curve::SigmoidCurve;
+use crate::error::ErrorCode;
+
+// Steepness is stored as an integer scaled by this factor (300_000 = 0.0003)
+pub const STEEPNESS_SCALE: u64 = 1_000_000_000;
+
+// Sigmoid pricing curve parameters (prices in 6 decimals)
+#[account]
+#[derive(InitSpace)]
+pub struct PricingParams {
+    pub price_max: u64, // Price per token for very small purchases
+    pub price_min: u64, // Price per token for very large purchases
+    pub midpoint: u64,  // Purchase size (tokens) at the middle of the discount curve
+    pub steepness: u64, // Controls how quickly the discount applies, scaled by STEEPNESS_SCALE
+    pub last_updated: i64,
+    pub bump: u8,
+}
+
+impl PricingParams {
+    pub fn curve(&self) -> SigmoidCurve {
+        SigmoidCurve {
+            price_max: self.price_max,
+            price_min: self.price_min,
+            midpoint: self.midpoint,
+            steepness: self.steepness,
+        }
+    }
+
+    pub fn validate(price_max: u64, price_min: u64, steepness: u64) -> Result<()> {
+        require!(price_min > 0, ErrorCode::InvalidPricingParams);
+        require!(price_min <= price_max, ErrorCode::InvalidPricingParams);
+        require!(steepness > 0, ErrorCode::InvalidPricingParams);
+        Ok(())
+    }
+}
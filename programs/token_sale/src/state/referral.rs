@@ -0,0 +1,46 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::state::buyer::PaymentCurrency;
+use crate::state::payment::MAX_PAYMENT_CURRENCIES;
+
+// Referral stats for one referrer, created by register_referrer
+#[account]
+#[derive(InitSpace)]
+pub struct ReferralRecord {
+    pub referrer: Pubkey,
+    pub upline: Pubkey, // The referrer's own referrer (default if none)
+    pub referred_count: u32,
+    pub referred_volume: u64, // Base units of the sale mint bought by referred buyers
+    pub reward_tokens: u64,   // Bonus tokens minted to the referrer
+    pub reward_sol: u64,      // Payment shares received, in lamports
+    pub reward_per_currency: [u64; MAX_PAYMENT_CURRENCIES], // Indexed like PaymentRegistry::currencies
+    pub bump: u8,
+}
+
+impl ReferralRecord {
+    // Account a referred purchase: `first_purchase` counts a newly referred buyer
+    pub fn record_referral(
+        &mut self,
+        first_purchase: bool,
+        amount: u64,
+        bonus_tokens: u64,
+        currency: PaymentCurrency,
+        payment_share: u64,
+    ) -> Result<()> {
+        if first_purchase {
+            self.referred_count = self.referred_count.checked_add(1).ok_or(ErrorCode::Overflow)?;
+        }
+        self.referred_volume = self.referred_volume.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+        self.reward_tokens = self.reward_tokens.checked_add(bonus_tokens).ok_or(ErrorCode::Overflow)?;
+
+        let reward = match currency {
+            PaymentCurrency::Sol => &mut self.reward_sol,
+            PaymentCurrency::Token(index) => self
+                .reward_per_currency
+                .get_mut(index as usize)
+                .ok_or(ErrorCode::CurrencyNotAccepted)?,
+        };
+        *reward = reward.checked_add(payment_share).ok_or(ErrorCode::Overflow)?;
+        Ok(())
+    }
+}
@@ -0,0 +1,140 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+pub const MAX_RECIPIENTS: usize = 5;
+const TOTAL_BPS: u64 = 10_000;
+
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct Recipient {
+    pub wallet: Pubkey,
+    pub bps: u16,
+}
+
+// How vault withdrawals are divided. Without this account withdrawals go to config.treasury.
+#[account]
+#[derive(InitSpace)]
+pub struct RevenueSplit {
+    #[max_len(MAX_RECIPIENTS)]
+    pub recipients: Vec<Recipient>,
+    pub bump: u8,
+}
+
+impl RevenueSplit {
+    // 1..=MAX_RECIPIENTS distinct wallets with non-zero shares summing to 10_000 bps
+    pub fn validate(recipients: &[Recipient]) -> Result<()> {
+        require!(
+            !recipients.is_empty() && recipients.len() <= MAX_RECIPIENTS,
+            ErrorCode::InvalidRevenueSplit
+        );
+
+        let mut total_bps = 0u64;
+        for (index, recipient) in recipients.iter().enumerate() {
+            require!(recipient.bps > 0, ErrorCode::InvalidRevenueSplit);
+            require!(recipient.wallet != Pubkey::default(), ErrorCode::InvalidRevenueSplit);
+            require!(
+                !recipients[..index].iter().any(|other| other.wallet == recipient.wallet),
+                ErrorCode::InvalidRevenueSplit
+            );
+            total_bps += recipient.bps as u64;
+        }
+        require!(total_bps == TOTAL_BPS, ErrorCode::InvalidRevenueSplit);
+        Ok(())
+    }
+
+    // Each recipient's part of `amount`, rounded down; the rounding remainder goes to
+    // the first recipient so the shares always add up to `amount`
+    pub fn shares(&self, amount: u64) -> Vec<u64> {
+        let mut shares: Vec<u64> = self
+            .recipients
+            .iter()
+            .map(|recipient| ((amount as u128 * recipient.bps as u128) / TOTAL_BPS as u128) as u64)
+            .collect();
+
+        let distributed = shares.iter().sum::<u64>();
+        if let Some(first) = shares.first_mut() {
+            *first += amount - distributed;
+        }
+        shares
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::assert_error;
+    use proptest::prelude::*;
+
+    fn recipients(bps: &[u16]) -> Vec<Recipient> {
+        bps.iter().map(|&bps| Recipient { wallet: Pubkey::new_unique(), bps }).collect()
+    }
+
+    fn split(bps: &[u16]) -> RevenueSplit {
+        RevenueSplit { recipients: recipients(bps), bump: 255 }
+    }
+
+    // 1..=MAX_RECIPIENTS non-zero shares summing to TOTAL_BPS, cut at distinct points
+    fn arb_bps() -> impl Strategy<Value = Vec<u16>> {
+        proptest::sample::subsequence((1..TOTAL_BPS as u16).collect::<Vec<_>>(), 0..MAX_RECIPIENTS).prop_map(|cuts| {
+            let mut bounds = vec![0];
+            bounds.extend(cuts);
+            bounds.push(TOTAL_BPS as u16);
+            bounds.windows(2).map(|pair| pair[1] - pair[0]).collect()
+        })
+    }
+
+    #[test]
+    fn bps_must_sum_to_total() {
+        RevenueSplit::validate(&recipients(&[10_000])).unwrap();
+        RevenueSplit::validate(&recipients(&[5_000, 3_000, 2_000])).unwrap();
+        assert_error(RevenueSplit::validate(&recipients(&[5_000, 4_999])), ErrorCode::InvalidRevenueSplit);
+        assert_error(RevenueSplit::validate(&recipients(&[5_000, 5_001])), ErrorCode::InvalidRevenueSplit);
+        assert_error(RevenueSplit::validate(&recipients(&[10_000, 0])), ErrorCode::InvalidRevenueSplit);
+    }
+
+    #[test]
+    fn duplicate_wallets_are_rejected() {
+        let mut duplicated = recipients(&[4_000, 3_000, 3_000]);
+        duplicated[2].wallet = duplicated[0].wallet;
+        assert_error(RevenueSplit::validate(&duplicated), ErrorCode::InvalidRevenueSplit);
+
+        let mut unset = recipients(&[5_000, 5_000]);
+        unset[1].wallet = Pubkey::default();
+        assert_error(RevenueSplit::validate(&unset), ErrorCode::InvalidRevenueSplit);
+    }
+
+    #[test]
+    fn recipient_count_is_bounded() {
+        assert_error(RevenueSplit::validate(&[]), ErrorCode::InvalidRevenueSplit);
+        RevenueSplit::validate(&recipients(&[2_000; MAX_RECIPIENTS])).unwrap();
+        let too_many = recipients(&[1_000, 1_000, 2_000, 2_000, 2_000, 2_000]);
+        assert_error(RevenueSplit::validate(&too_many), ErrorCode::InvalidRevenueSplit);
+    }
+
+    #[test]
+    fn remainder_goes_to_first_recipient() {
+        assert_eq!(split(&[3_333, 3_333, 3_334]).shares(10), vec![4, 3, 3]);
+        assert_eq!(split(&[5_000, 5_000]).shares(1), vec![1, 0]);
+        assert_eq!(split(&[2_500, 7_500]).shares(0), vec![0, 0]);
+        assert_eq!(split(&[10_000]).shares(u64::MAX), vec![u64::MAX]);
+    }
+
+    proptest! {
+        #[test]
+        fn shares_sum_to_amount(bps in arb_bps(), amount in any::<u64>()) {
+            let split = split(&bps);
+            prop_assert!(RevenueSplit::validate(&split.recipients).is_ok());
+
+            let shares = split.shares(amount);
+            prop_assert_eq!(shares.len(), bps.len());
+            prop_assert_eq!(shares.iter().map(|&share| share as u128).sum::<u128>(), amount as u128);
+
+            // Everyone but the first recipient gets exactly their rounded-down share
+            let floor = |bps: u16| ((amount as u128 * bps as u128) / TOTAL_BPS as u128) as u64;
+            for (share, &bps) in shares.iter().zip(&bps).skip(1) {
+                prop_assert_eq!(*share, floor(bps));
+            }
+            prop_assert!(shares[0] >= floor(bps[0]));
+            prop_assert!(shares[0] - floor(bps[0]) < bps.len() as u64);
+        }
+    }
+}
@@ -0,0 +1,227 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+
+pub const MAX_ROUNDS: usize = 10;
+
+// A single sale round - owner-configured window, token cap and discount
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct Round {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub token_cap: u64,    // Base units of the sale mint
+    pub discount_bps: u16, // Discount off the base curve price (1000 = 10% cheaper)
+    pub tokens_sold: u64,  // Base units of the sale mint
+    pub vesting_cliff: i64,    // Seconds after round end before anything unlocks
+    pub vesting_duration: i64, // Linear release period after the cliff
+    pub min_purchase: u64,     // Smallest single purchase in base units (0 = no minimum)
+    pub max_per_wallet: u64,   // Most one wallet may buy in this round (0 = no cap)
+    pub allowlist_root: [u8; 32], // Merkle root of (wallet, allocation) leaves (zero = open round)
+    pub allowlist_leaf_version: u8, // Leaf format of allowlist_root, see instructions::airdrop
+}
+
+impl Round {
+    pub fn remaining_tokens(&self) -> u64 {
+        self.token_cap.saturating_sub(self.tokens_sold)
+    }
+
+    pub fn has_started(&self, now: i64) -> bool {
+        now >= self.start_time
+    }
+
+    // How much more a wallet that already bought `bought` may buy this round
+    pub fn wallet_allowance(&self, bought: u64) -> u64 {
+        if self.max_per_wallet == 0 {
+            return u64::MAX;
+        }
+        self.max_per_wallet.saturating_sub(bought)
+    }
+
+    pub fn has_allowlist(&self) -> bool {
+        self.allowlist_root != [0u8; 32]
+    }
+
+    // Rounds without vesting terms mint straight to the buyer
+    pub fn has_vesting(&self) -> bool {
+        self.vesting_cliff > 0 || self.vesting_duration > 0
+    }
+}
+
+// Owner-supplied settings for a new or edited round
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq)]
+pub struct RoundParams {
+    pub start_time: i64,
+    pub end_time: i64,
+    pub token_cap: u64,
+    pub discount_bps: u16,
+    pub vesting_cliff: i64,
+    pub vesting_duration: i64,
+    pub min_purchase: u64,
+    pub max_per_wallet: u64,
+}
+
+impl From<RoundParams> for Round {
+    fn from(params: RoundParams) -> Self {
+        Round {
+            start_time: params.start_time,
+            end_time: params.end_time,
+            token_cap: params.token_cap,
+            discount_bps: params.discount_bps,
+            tokens_sold: 0,
+            vesting_cliff: params.vesting_cliff,
+            vesting_duration: params.vesting_duration,
+            min_purchase: params.min_purchase,
+            max_per_wallet: params.max_per_wallet,
+            allowlist_root: [0u8; 32],
+            allowlist_leaf_version: 0,
+        }
+    }
+}
+
+// Ordered, non-overlapping list of sale rounds
+#[account]
+#[derive(InitSpace)]
+pub struct RoundSchedule {
+    #[max_len(MAX_ROUNDS)]
+    pub rounds: Vec<Round>,
+    pub bump: u8,
+}
+
+impl RoundSchedule {
+    // Index of the round active at `now`
+    pub fn current_round_index(&self, now: i64) -> Result<usize> {
+        if let Some(index) = self
+            .rounds
+            .iter()
+            .position(|round| now >= round.start_time && now < round.end_time)
+        {
+            return Ok(index);
+        }
+
+        match (self.rounds.first(), self.rounds.last()) {
+            (Some(first), _) if now < first.start_time => err!(ErrorCode::SaleNotStarted),
+            (_, Some(last)) if now < last.end_time => err!(ErrorCode::NoActiveRound),
+            (Some(_), _) => err!(ErrorCode::SaleEnded),
+            _ => err!(ErrorCode::SaleNotStarted),
+        }
+    }
+
+    // Start of the first round (0 if no rounds are configured)
+    pub fn sale_start_time(&self) -> i64 {
+        self.rounds.first().map(|round| round.start_time).unwrap_or(0)
+    }
+
+    // End of the last round (0 if no rounds are configured)
+    pub fn sale_end_time(&self) -> i64 {
+        self.rounds.last().map(|round| round.end_time).unwrap_or(0)
+    }
+
+    pub fn total_tokens_sold(&self) -> u64 {
+        self.rounds.iter().fold(0u64, |sum, round| sum.saturating_add(round.tokens_sold))
+    }
+
+    pub fn total_token_cap(&self) -> u64 {
+        self.rounds.iter().fold(0u64, |sum, round| sum.saturating_add(round.token_cap))
+    }
+
+    // Checks that `round` fits at `index` without overlapping its neighbours
+    pub fn validate_round_at(&self, index: usize, round: &Round) -> Result<()> {
+        require!(round.start_time < round.end_time, ErrorCode::InvalidRound);
+        require!(round.token_cap > 0, ErrorCode::InvalidRound);
+        require!(round.discount_bps < 10_000, ErrorCode::InvalidRound);
+        require!(round.vesting_cliff >= 0 && round.vesting_duration >= 0, ErrorCode::InvalidRound);
+        require!(round.min_purchase <= round.token_cap, ErrorCode::InvalidRound);
+        require!(
+            round.max_per_wallet == 0 || round.min_purchase <= round.max_per_wallet,
+            ErrorCode::InvalidRound
+        );
+
+        if index > 0 {
+            if let Some(previous) = self.rounds.get(index - 1) {
+                require!(round.start_time >= previous.end_time, ErrorCode::RoundOverlap);
+            }
+        }
+        if let Some(next) = self.rounds.get(index + 1) {
+            require!(round.end_time <= next.start_time, ErrorCode::RoundOverlap);
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::assert_error;
+
+    fn round(start_time: i64, end_time: i64) -> Round {
+        Round::from(RoundParams {
+            start_time,
+            end_time,
+            token_cap: 1_000,
+            discount_bps: 0,
+            vesting_cliff: 0,
+            vesting_duration: 0,
+            min_purchase: 0,
+            max_per_wallet: 0,
+        })
+    }
+
+    // [100, 200) and [200, 300) back to back, a gap, then [400, 500)
+    fn schedule() -> RoundSchedule {
+        RoundSchedule { rounds: vec![round(100, 200), round(200, 300), round(400, 500)], bump: 255 }
+    }
+
+    #[test]
+    fn rounds_are_half_open() {
+        let schedule = schedule();
+        assert_eq!(schedule.current_round_index(100).unwrap(), 0);
+        assert_eq!(schedule.current_round_index(199).unwrap(), 0);
+        assert_eq!(schedule.current_round_index(200).unwrap(), 1);
+        assert_eq!(schedule.current_round_index(299).unwrap(), 1);
+        assert_eq!(schedule.current_round_index(400).unwrap(), 2);
+        assert_eq!(schedule.current_round_index(499).unwrap(), 2);
+    }
+
+    #[test]
+    fn no_round_outside_the_windows() {
+        let schedule = schedule();
+        assert_error(schedule.current_round_index(99), ErrorCode::SaleNotStarted);
+        assert_error(schedule.current_round_index(300), ErrorCode::NoActiveRound);
+        assert_error(schedule.current_round_index(399), ErrorCode::NoActiveRound);
+        assert_error(schedule.current_round_index(500), ErrorCode::SaleEnded);
+        assert_error(schedule.current_round_index(i64::MAX), ErrorCode::SaleEnded);
+
+        let empty = RoundSchedule { rounds: Vec::new(), bump: 255 };
+        assert_error(empty.current_round_index(0), ErrorCode::SaleNotStarted);
+    }
+
+    #[test]
+    fn edited_round_must_fit_between_neighbours() {
+        let schedule = schedule();
+        schedule.validate_round_at(1, &round(200, 400)).unwrap();
+        schedule.validate_round_at(1, &round(250, 260)).unwrap();
+        assert_error(schedule.validate_round_at(1, &round(199, 300)), ErrorCode::RoundOverlap);
+        assert_error(schedule.validate_round_at(1, &round(200, 401)), ErrorCode::RoundOverlap);
+        // First and last rounds only have one neighbour
+        schedule.validate_round_at(0, &round(i64::MIN, 200)).unwrap();
+        assert_error(schedule.validate_round_at(0, &round(0, 201)), ErrorCode::RoundOverlap);
+        schedule.validate_round_at(2, &round(300, i64::MAX)).unwrap();
+        assert_error(schedule.validate_round_at(2, &round(299, 500)), ErrorCode::RoundOverlap);
+    }
+
+    #[test]
+    fn appended_round_must_start_after_the_last() {
+        let schedule = schedule();
+        schedule.validate_round_at(3, &round(500, 600)).unwrap();
+        assert_error(schedule.validate_round_at(3, &round(499, 600)), ErrorCode::RoundOverlap);
+    }
+
+    #[test]
+    fn invalid_round_settings_are_rejected() {
+        let schedule = schedule();
+        assert_error(schedule.validate_round_at(3, &round(600, 600)), ErrorCode::InvalidRound);
+        let no_cap = Round { token_cap: 0, ..round(500, 600) };
+        assert_error(schedule.validate_round_at(3, &no_cap), ErrorCode::InvalidRound);
+        let full_discount = Round { discount_bps: 10_000, ..round(500, 600) };
+        assert_error(schedule.validate_round_at(3, &full_discount), ErrorCode::InvalidRound);
+    }
+}
@@ -0,0 +1,196 @@
+use anchor_lang::prelude::*;
+use crate::error::ErrorCode;
+use crate::state::round::{Round, MAX_ROUNDS};
+
+// Tokens bought in one round, with that round's vesting terms snapshotted at purchase.
+// Nothing unlocks before `vesting_start + cliff`; the full amount then releases
+// linearly over `duration` seconds.
+#[derive(AnchorSerialize, AnchorDeserialize, Clone, Copy, Debug, PartialEq, InitSpace)]
+pub struct VestingEntry {
+    pub round_start_time: i64, // Identifies the round - started rounds can't be edited
+    pub vesting_start: i64,    // Round end time
+    pub cliff: i64,
+    pub duration: i64,
+    pub total: u64,   // Base units of the sale mint
+    pub claimed: u64, // Base units of the sale mint
+}
+
+impl VestingEntry {
+    // Amount unlocked at `now`, claimed or not
+    pub fn unlocked(&self, now: i64) -> u64 {
+        let release_start = self.vesting_start.saturating_add(self.cliff);
+        if now < release_start {
+            return 0;
+        }
+
+        let elapsed = now.saturating_sub(release_start);
+        if elapsed >= self.duration {
+            return self.total;
+        }
+
+        // elapsed < duration here, so this never exceeds total
+        ((self.total as u128 * elapsed as u128) / self.duration as u128) as u64
+    }
+
+    pub fn claimable(&self, now: i64) -> u64 {
+        self.unlocked(now).saturating_sub(self.claimed)
+    }
+}
+
+// Per-buyer vesting balances, one entry per round bought in
+#[account]
+#[derive(InitSpace)]
+pub struct VestingAccount {
+    pub buyer: Pubkey,
+    #[max_len(MAX_ROUNDS)]
+    pub entries: Vec<VestingEntry>,
+    pub bump: u8,
+}
+
+impl VestingAccount {
+    // Credit `amount` bought in `round` to the buyer's schedule
+    pub fn credit(&mut self, round: &Round, amount: u64) -> Result<()> {
+        if let Some(entry) = self
+            .entries
+            .iter_mut()
+            .find(|entry| entry.round_start_time == round.start_time)
+        {
+            entry.total = entry.total.checked_add(amount).ok_or(ErrorCode::Overflow)?;
+            return Ok(());
+        }
+
+        require!(self.entries.len() < MAX_ROUNDS, ErrorCode::TooManyRounds);
+        self.entries.push(VestingEntry {
+            round_start_time: round.start_time,
+            vesting_start: round.end_time,
+            cliff: round.vesting_cliff,
+            duration: round.vesting_duration,
+            total: amount,
+            claimed: 0,
+        });
+        Ok(())
+    }
+
+    // Marks everything unlocked at `now` as claimed and returns the amount
+    pub fn claim(&mut self, now: i64) -> Result<u64> {
+        let mut amount = 0u64;
+        for entry in self.entries.iter_mut() {
+            let claimable = entry.claimable(now);
+            entry.claimed = entry.claimed.checked_add(claimable).ok_or(ErrorCode::Overflow)?;
+            amount = amount.checked_add(claimable).ok_or(ErrorCode::Overflow)?;
+        }
+        Ok(amount)
+    }
+
+    pub fn total_unclaimed(&self) -> u64 {
+        self.entries
+            .iter()
+            .fold(0u64, |sum, entry| sum.saturating_add(entry.total - entry.claimed))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::RoundParams;
+    use proptest::prelude::*;
+
+    // Round ending at 1_000, releasing over `duration` seconds after `cliff`
+    fn round(start_time: i64, cliff: i64, duration: i64) -> Round {
+        Round::from(RoundParams {
+            start_time,
+            end_time: 1_000,
+            token_cap: u64::MAX,
+            discount_bps: 0,
+            vesting_cliff: cliff,
+            vesting_duration: duration,
+            min_purchase: 0,
+            max_per_wallet: 0,
+        })
+    }
+
+    fn account(rounds: &[(Round, u64)]) -> VestingAccount {
+        let mut account = VestingAccount { buyer: Pubkey::new_unique(), entries: Vec::new(), bump: 255 };
+        for (round, amount) in rounds {
+            account.credit(round, *amount).unwrap();
+        }
+        account
+    }
+
+    #[test]
+    fn nothing_unlocks_before_the_cliff() {
+        let entry = account(&[(round(0, 100, 400), 1_000)]).entries[0];
+        assert_eq!(entry.unlocked(0), 0);
+        assert_eq!(entry.unlocked(1_099), 0);
+        // Release starts at the cliff with nothing elapsed yet
+        assert_eq!(entry.unlocked(1_100), 0);
+        assert_eq!(entry.unlocked(1_101), 2);
+    }
+
+    #[test]
+    fn release_is_linear_after_the_cliff() {
+        let entry = account(&[(round(0, 100, 400), 1_000)]).entries[0];
+        assert_eq!(entry.unlocked(1_200), 250);
+        assert_eq!(entry.unlocked(1_300), 500);
+        assert_eq!(entry.unlocked(1_499), 997);
+        assert_eq!(entry.unlocked(1_500), 1_000);
+        assert_eq!(entry.unlocked(i64::MAX), 1_000);
+    }
+
+    #[test]
+    fn zero_duration_unlocks_everything_at_the_cliff() {
+        let entry = account(&[(round(0, 100, 0), 1_000)]).entries[0];
+        assert_eq!(entry.unlocked(1_099), 0);
+        assert_eq!(entry.unlocked(1_100), 1_000);
+
+        let no_cliff = account(&[(round(0, 0, 0), 1_000)]).entries[0];
+        assert_eq!(no_cliff.unlocked(999), 0);
+        assert_eq!(no_cliff.unlocked(1_000), 1_000);
+    }
+
+    #[test]
+    fn credit_merges_purchases_in_the_same_round() {
+        let first = round(0, 100, 400);
+        let second = round(500, 0, 200);
+        let mut account = account(&[(first, 300), (second, 50)]);
+        account.credit(&first, 700).unwrap();
+
+        assert_eq!(account.entries.len(), 2);
+        assert_eq!(account.entries[0].total, 1_000);
+        assert_eq!(account.entries[1].total, 50);
+        assert_eq!(account.total_unclaimed(), 1_050);
+        assert!(account.credit(&first, u64::MAX).is_err());
+    }
+
+    #[test]
+    fn claims_only_pay_out_new_unlocks() {
+        let mut account = account(&[(round(0, 100, 400), 1_000), (round(500, 0, 0), 40)]);
+        assert_eq!(account.claim(1_099).unwrap(), 40);
+        assert_eq!(account.claim(1_300).unwrap(), 500);
+        assert_eq!(account.claim(1_300).unwrap(), 0);
+        assert_eq!(account.claim(2_000).unwrap(), 500);
+        assert_eq!(account.claim(3_000).unwrap(), 0);
+        assert_eq!(account.total_unclaimed(), 0);
+    }
+
+    proptest! {
+        #[test]
+        fn repeated_claims_never_exceed_total(
+            total in any::<u64>(),
+            cliff in 0i64..1_000_000,
+            duration in 0i64..1_000_000,
+            mut times in proptest::collection::vec(0i64..3_000_000, 1..20),
+        ) {
+            times.sort_unstable();
+            let mut account = account(&[(round(0, cliff, duration), total)]);
+            let mut claimed = 0u128;
+            for now in times {
+                claimed += account.claim(now).unwrap() as u128;
+                prop_assert!(claimed <= total as u128);
+                prop_assert_eq!(account.entries[0].claimed as u128, claimed);
+            }
+            claimed += account.claim(i64::MAX).unwrap() as u128;
+            prop_assert_eq!(claimed, total as u128);
+        }
+    }
+}
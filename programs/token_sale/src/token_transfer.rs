@@ -0,0 +1,178 @@
+use anchor_lang::prelude::*;
+use anchor_spl::token_2022::spl_token_2022::{
+    self,
+    extension::{transfer_fee::{TransferFee, TransferFeeConfig}, BaseStateWithExtensions, StateWithExtensions},
+    onchain,
+    state::Mint,
+};
+use crate::error::ErrorCode;
+
+// Payment-side token transfers shared by buys, refunds and withdrawals. They work for
+// both Token and Token-2022 mints, including Token-2022 transfer fees and transfer hooks.
+
+// The mint's transfer fee for the current epoch, or None if it charges none
+pub fn current_transfer_fee(mint: &AccountInfo) -> Result<Option<TransferFee>> {
+    transfer_fee_at(mint, Clock::get()?.epoch)
+}
+
+fn transfer_fee_at(mint: &AccountInfo, epoch: u64) -> Result<Option<TransferFee>> {
+    if *mint.owner != spl_token_2022::ID {
+        return Ok(None);
+    }
+    let data = mint.try_borrow_data()?;
+    let mint_state = StateWithExtensions::<Mint>::unpack(&data)?;
+    let Ok(fee_config) = mint_state.get_extension::<TransferFeeConfig>() else {
+        return Ok(None);
+    };
+    Ok(Some(*fee_config.get_epoch_fee(epoch)))
+}
+
+// Amount to send so that `net` arrives after the transfer fee
+pub fn gross_up(fee: Option<&TransferFee>, net: u64) -> Result<u64> {
+    let Some(fee) = fee else {
+        return Ok(net);
+    };
+    let fee_amount = fee.calculate_inverse_fee(net).ok_or(ErrorCode::Overflow)?;
+    net.checked_add(fee_amount).ok_or(error!(ErrorCode::Overflow))
+}
+
+// transfer_checked that appends the mint's transfer-hook accounts, resolved from
+// `additional_accounts` (the instruction's remaining accounts)
+#[allow(clippy::too_many_arguments)]
+pub fn transfer_checked<'info>(
+    token_program: &AccountInfo<'info>,
+    from: AccountInfo<'info>,
+    mint: AccountInfo<'info>,
+    to: AccountInfo<'info>,
+    authority: AccountInfo<'info>,
+    additional_accounts: &[AccountInfo<'info>],
+    amount: u64,
+    decimals: u8,
+    signer_seeds: &[&[&[u8]]],
+) -> Result<()> {
+    onchain::invoke_transfer_checked(
+        token_program.key,
+        from,
+        mint,
+        to,
+        authority,
+        additional_accounts,
+        amount,
+        decimals,
+        signer_seeds,
+    )
+    .map_err(Into::into)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use anchor_lang::solana_program::program_pack::Pack;
+    use anchor_spl::token_2022::spl_token_2022::extension::{ExtensionType, StateWithExtensionsMut, BaseStateWithExtensionsMut};
+    use proptest::prelude::*;
+
+    fn fee(transfer_fee_basis_points: u16, maximum_fee: u64) -> TransferFee {
+        TransferFee {
+            epoch: 0.into(),
+            maximum_fee: maximum_fee.into(),
+            transfer_fee_basis_points: transfer_fee_basis_points.into(),
+        }
+    }
+
+    // Initialized Token-2022 mint data, with a transfer fee switching from `older` to
+    // `newer` at epoch 10 if given
+    fn mint_data(fees: Option<(TransferFee, TransferFee)>) -> Vec<u8> {
+        let extensions: &[ExtensionType] = if fees.is_some() { &[ExtensionType::TransferFeeConfig] } else { &[] };
+        let len = ExtensionType::try_calculate_account_len::<Mint>(extensions).unwrap();
+        let mut data = vec![0; len];
+        let mut state = StateWithExtensionsMut::<Mint>::unpack_uninitialized(&mut data).unwrap();
+        if let Some((older, newer)) = fees {
+            let config = state.init_extension::<TransferFeeConfig>(true).unwrap();
+            config.older_transfer_fee = older;
+            config.newer_transfer_fee = TransferFee { epoch: 10.into(), ..newer };
+        }
+        state.base = Mint { decimals: 6, is_initialized: true, ..Mint::default() };
+        state.pack_base();
+        state.init_account_type().unwrap();
+        data
+    }
+
+    fn fee_at(data: &mut [u8], owner: &Pubkey, epoch: u64) -> Option<TransferFee> {
+        let key = Pubkey::new_unique();
+        let mut lamports = 0;
+        let mint = AccountInfo::new(&key, false, false, &mut lamports, data, owner, false, 0);
+        transfer_fee_at(&mint, epoch).unwrap()
+    }
+
+    // What arrives when sending `gross`
+    fn received(fee: &TransferFee, gross: u64) -> u64 {
+        gross - fee.calculate_fee(gross).unwrap()
+    }
+
+    #[test]
+    fn no_fee_passes_amount_through() {
+        assert_eq!(gross_up(None, 1_000).unwrap(), 1_000);
+        assert_eq!(gross_up(Some(&fee(0, 5_000)), 1_000).unwrap(), 1_000);
+        assert_eq!(gross_up(Some(&fee(0, 5_000)), u64::MAX).unwrap(), u64::MAX);
+        assert_eq!(gross_up(Some(&fee(100, 5_000)), 0).unwrap(), 0);
+    }
+
+    #[test]
+    fn gross_up_covers_the_fee() {
+        // 1% of 1_000_000 / 0.99 = 1_010_102 gross, 10_102 fee
+        let one_percent = fee(100, u64::MAX);
+        let gross = gross_up(Some(&one_percent), 1_000_000).unwrap();
+        assert_eq!(gross, 1_010_102);
+        assert_eq!(received(&one_percent, gross), 1_000_000);
+        assert!(received(&one_percent, gross - 1) < 1_000_000);
+    }
+
+    #[test]
+    fn gross_up_respects_maximum_fee() {
+        // 1% would be 10_102, clamped to 500
+        let capped = fee(100, 500);
+        let gross = gross_up(Some(&capped), 1_000_000).unwrap();
+        assert_eq!(gross, 1_000_500);
+        assert_eq!(capped.calculate_fee(gross), Some(500));
+        assert_eq!(received(&capped, gross), 1_000_000);
+
+        // A 100% fee only ever takes the maximum
+        let everything = fee(10_000, 500);
+        assert_eq!(gross_up(Some(&everything), 1_000).unwrap(), 1_500);
+        assert_eq!(received(&everything, 1_500), 1_000);
+    }
+
+    #[test]
+    fn gross_up_overflow_is_an_error() {
+        assert!(gross_up(Some(&fee(100, 500)), u64::MAX).is_err());
+        assert!(gross_up(Some(&fee(10_000, u64::MAX)), 1).is_err());
+    }
+
+    #[test]
+    fn transfer_fee_follows_the_epoch() {
+        let token_2022 = spl_token_2022::ID;
+        let mut data = mint_data(Some((fee(100, 1_000), fee(250, 2_000))));
+        assert_eq!(fee_at(&mut data, &token_2022, 9), Some(fee(100, 1_000)));
+        assert_eq!(fee_at(&mut data, &token_2022, 10), Some(TransferFee { epoch: 10.into(), ..fee(250, 2_000) }));
+    }
+
+    #[test]
+    fn mints_without_a_fee_have_none() {
+        let mut plain = mint_data(None);
+        assert_eq!(fee_at(&mut plain, &spl_token_2022::ID, 0), None);
+        // Legacy Token mints are never read
+        let mut legacy = vec![0; Mint::LEN];
+        assert_eq!(fee_at(&mut legacy, &anchor_spl::token::ID, 0), None);
+    }
+
+    proptest! {
+        #[test]
+        fn gross_up_always_delivers_net(bps in 0u16..=10_000, maximum_fee in 0u64..1_000_000_000, net in 0u64..1_000_000_000_000_000) {
+            let fee = fee(bps, maximum_fee);
+            let gross = gross_up(Some(&fee), net).unwrap();
+            let charged = fee.calculate_fee(gross).unwrap();
+            prop_assert!(charged <= maximum_fee);
+            prop_assert!(gross - charged >= net);
+        }
+    }
+}
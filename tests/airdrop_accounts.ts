@@ -0,0 +1,344 @@
+import * as anchor from "@coral-xyz/anchor";
+import { Program, BN } from "@coral-xyz/anchor";
+import { TokenSale } from "../target/types/token_sale";
+import {
+  Keypair,
+  LAMPORTS_PER_SOL,
+  PublicKey,
+  SystemProgram,
+} from "@solana/web3.js";
+import {
+  ASSOCIATED_TOKEN_PROGRAM_ID,
+  TOKEN_2022_PROGRAM_ID,
+  createMint,
+  getAssociatedTokenAddressSync,
+} from "@solana/spl-token";
+import { sha256 } from "js-sha256";
+import { expect } from "chai";
+
+// Negative-path tests for the airdrop accounts: every spoofed account must be rejected
+describe("airdrop account validation", () => {
+  anchor.setProvider(anchor.AnchorProvider.env());
+
+  const program = anchor.workspace.TokenSale as Program<TokenSale>;
+  const provider = anchor.getProvider() as anchor.AnchorProvider;
+  const connection = provider.connection;
+  const owner = (provider.wallet as anchor.Wallet).payer;
+
+  const claimer = Keypair.generate();
+  const attacker = Keypair.generate();
+
+  const campaignId = new BN(Date.now());
+  const otherCampaignId = campaignId.addn(1);
+  const maxPerClaim = new BN(100);
+  const budget = new BN(1_000);
+
+  const [configPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("token_sale_config")],
+    program.programId
+  );
+  const [mintAuthorityPda] = PublicKey.findProgramAddressSync(
+    [Buffer.from("mint")],
+    program.programId
+  );
+
+  const airdropConfigPda = (id: BN) =>
+    PublicKey.findProgramAddressSync(
+      [Buffer.from("airdrop-config"), id.toArrayLike(Buffer, "le", 8)],
+      program.programId
+    )[0];
+
+  const claimBitmapPda = (id: BN, chunk: number) => {
+    const chunkBytes = Buffer.alloc(4);
+    chunkBytes.writeUInt32LE(chunk);
+    return PublicKey.findProgramAddressSync(
+      [Buffer.from("claim-bitmap"), id.toArrayLike(Buffer, "le", 8), chunkBytes],
+      program.programId
+    )[0];
+  };
+
+  // v1 leaf: sha256(0x00 || program_id || campaign_id || index || wallet || amount), u64s LE;
+  // nodes: sha256(0x01 || sorted pair)
+  const leaf = (index: number, wallet: PublicKey, amount: BN) =>
+    Buffer.from(
+      sha256.digest(
+        Buffer.concat([
+          Buffer.from([0x00]),
+          program.programId.toBuffer(),
+          campaignId.toArrayLike(Buffer, "le", 8),
+          new BN(index).toArrayLike(Buffer, "le", 8),
+          wallet.toBuffer(),
+          amount.toArrayLike(Buffer, "le", 8),
+        ])
+      )
+    );
+
+  const hashPair = (a: Buffer, b: Buffer) =>
+    Buffer.from(
+      sha256.digest(
+        Buffer.concat([Buffer.from([0x01]), ...(Buffer.compare(a, b) < 0 ? [a, b] : [b, a])])
+      )
+    );
+
+  // Two leaves: a valid claim and one above the per-claim maximum
+  const validAmount = new BN(50);
+  const oversizedAmount = new BN(500);
+  const validLeaf = leaf(0, claimer.publicKey, validAmount);
+  const oversizedLeaf = leaf(1, claimer.publicKey, oversizedAmount);
+  const root = hashPair(validLeaf, oversizedLeaf);
+
+  let saleMint: PublicKey;
+  let foreignMint: PublicKey;
+
+  const expectError = async (promise: Promise<unknown>, code: string) => {
+    try {
+      await promise;
+    } catch (err) {
+      const anchorCode = (err as anchor.AnchorError).error?.errorCode?.code;
+      expect(anchorCode ?? String(err)).to.contain(code);
+      return;
+    }
+    expect.fail(`expected ${code}`);
+  };
+
+  const setMerkleRoot = (id: BN) => {
+    const now = Math.floor(Date.now() / 1000);
+    return program.methods.setMerkleRoot(
+      id,
+      Array.from(root),
+      1,
+      maxPerClaim,
+      budget,
+      new BN(10),
+      new BN(now - 60),
+      new BN(now + 3600)
+    );
+  };
+
+  const claimAccounts = (mint: PublicKey) => ({
+    claimer: claimer.publicKey,
+    saleConfig: configPda,
+    airdropConfig: airdropConfigPda(campaignId),
+    claimBitmap: claimBitmapPda(campaignId, 0),
+    mint,
+    claimerAta: getAssociatedTokenAddressSync(
+      mint,
+      claimer.publicKey,
+      false,
+      TOKEN_2022_PROGRAM_ID
+    ),
+    mintAuthorityPda,
+    token2022Program: TOKEN_2022_PROGRAM_ID,
+    systemProgram: SystemProgram.programId,
+    associatedTokenProgram: ASSOCIATED_TOKEN_PROGRAM_ID,
+  });
+
+  before(async () => {
+    for (const kp of [claimer, attacker]) {
+      const sig = await connection.requestAirdrop(kp.publicKey, 2 * LAMPORTS_PER_SOL);
+      await connection.confirmTransaction(sig, "confirmed");
+    }
+
+    const existing = await program.account.config.fetchNullable(configPda);
+    if (existing) {
+      saleMint = existing.mint;
+    } else {
+      saleMint = await createMint(
+        connection, owner, mintAuthorityPda, null, 6,
+        Keypair.generate(), undefined, TOKEN_2022_PROGRAM_ID
+      );
+      await program.methods
+        .initialize()
+        .accountsPartial({
+          config: configPda,
+          payer: owner.publicKey,
+          treasury: owner.publicKey,
+          mint: saleMint,
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+
+    // Same mint authority PDA as the sale mint, so only the address check stops it
+    foreignMint = await createMint(
+      connection, owner, mintAuthorityPda, null, 6,
+      Keypair.generate(), undefined, TOKEN_2022_PROGRAM_ID
+    );
+
+    for (const id of [campaignId, otherCampaignId]) {
+      await setMerkleRoot(id)
+        .accountsPartial({
+          authority: owner.publicKey,
+          saleConfig: configPda,
+          config: airdropConfigPda(id),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+      await program.methods
+        .initClaimBitmap(id, 0)
+        .accountsPartial({
+          authority: owner.publicKey,
+          saleConfig: configPda,
+          airdropConfig: airdropConfigPda(id),
+          claimBitmap: claimBitmapPda(id, 0),
+          systemProgram: SystemProgram.programId,
+        })
+        .rpc();
+    }
+  });
+
+  describe("set_merkle_root", () => {
+    it("rejects a sale config that is not owned by the program", async () => {
+      await expectError(
+        setMerkleRoot(campaignId)
+          .accountsPartial({
+            authority: attacker.publicKey,
+            saleConfig: attacker.publicKey,
+            config: airdropConfigPda(campaignId),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker])
+          .rpc(),
+        "AccountOwnedByWrongProgram"
+      );
+    });
+
+    it("rejects a program account of another type as sale config", async () => {
+      await expectError(
+        setMerkleRoot(campaignId)
+          .accountsPartial({
+            authority: owner.publicKey,
+            saleConfig: airdropConfigPda(otherCampaignId),
+            config: airdropConfigPda(campaignId),
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc(),
+        "AccountDiscriminatorMismatch"
+      );
+    });
+
+    it("rejects an airdrop config derived for another campaign", async () => {
+      await expectError(
+        setMerkleRoot(campaignId)
+          .accountsPartial({
+            authority: owner.publicKey,
+            saleConfig: configPda,
+            config: airdropConfigPda(otherCampaignId),
+            systemProgram: SystemProgram.programId,
+          })
+          .rpc(),
+        "ConstraintSeeds"
+      );
+    });
+
+    it("rejects a signer that is not the sale owner", async () => {
+      await expectError(
+        setMerkleRoot(campaignId)
+          .accountsPartial({
+            authority: attacker.publicKey,
+            saleConfig: configPda,
+            config: airdropConfigPda(campaignId),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker])
+          .rpc(),
+        "Unauthorized"
+      );
+    });
+  });
+
+  describe("init_claim_bitmap", () => {
+    it("rejects a spoofed sale config", async () => {
+      await expectError(
+        program.methods
+          .initClaimBitmap(campaignId, 1)
+          .accountsPartial({
+            authority: attacker.publicKey,
+            saleConfig: attacker.publicKey,
+            airdropConfig: airdropConfigPda(campaignId),
+            claimBitmap: claimBitmapPda(campaignId, 1),
+            systemProgram: SystemProgram.programId,
+          })
+          .signers([attacker])
+          .rpc(),
+        "AccountOwnedByWrongProgram"
+      );
+    });
+  });
+
+  describe("claim", () => {
+    const claim = (amount: BN, index: number, proof: Buffer[]) =>
+      program.methods.claim(campaignId, new BN(index), amount, proof.map((p) => Array.from(p)));
+
+    it("rejects a foreign mint sharing the mint authority", async () => {
+      await expectError(
+        claim(validAmount, 0, [oversizedLeaf])
+          .accountsPartial(claimAccounts(foreignMint))
+          .signers([claimer])
+          .rpc(),
+        "InvalidMint"
+      );
+    });
+
+    it("rejects a sale config that is not owned by the program", async () => {
+      await expectError(
+        claim(validAmount, 0, [oversizedLeaf])
+          .accountsPartial({ ...claimAccounts(saleMint), saleConfig: claimer.publicKey })
+          .signers([claimer])
+          .rpc(),
+        "AccountOwnedByWrongProgram"
+      );
+    });
+
+    it("rejects an airdrop config from another campaign", async () => {
+      await expectError(
+        claim(validAmount, 0, [oversizedLeaf])
+          .accountsPartial({
+            ...claimAccounts(saleMint),
+            airdropConfig: airdropConfigPda(otherCampaignId),
+          })
+          .signers([claimer])
+          .rpc(),
+        "ConstraintSeeds"
+      );
+    });
+
+    it("rejects a claim bitmap from another campaign", async () => {
+      await expectError(
+        claim(validAmount, 0, [oversizedLeaf])
+          .accountsPartial({
+            ...claimAccounts(saleMint),
+            claimBitmap: claimBitmapPda(otherCampaignId, 0),
+          })
+          .signers([claimer])
+          .rpc(),
+        "ConstraintSeeds"
+      );
+    });
+
+    it("rejects an amount above the per-claim maximum", async () => {
+      await expectError(
+        claim(oversizedAmount, 1, [validLeaf])
+          .accountsPartial(claimAccounts(saleMint))
+          .signers([claimer])
+          .rpc(),
+        "AirdropAmountExceeded"
+      );
+    });
+
+    it("accepts a valid claim once and rejects the replay", async () => {
+      await claim(validAmount, 0, [oversizedLeaf])
+        .accountsPartial(claimAccounts(saleMint))
+        .signers([claimer])
+        .rpc();
+
+      await expectError(
+        claim(validAmount, 0, [oversizedLeaf])
+          .accountsPartial(claimAccounts(saleMint))
+          .signers([claimer])
+          .rpc(),
+        "AlreadyClaimed"
+      );
+    });
+  });
+});